@@ -17,3 +17,15 @@ CREATE TABLE items(
         ON DELETE CASCADE
         ON UPDATE CASCADE
 );
+
+CREATE TABLE history(
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    itemid INTEGER NOT NULL,
+    itemname VARCHAR(256) NOT NULL,
+    topicname VARCHAR(256) NOT NULL,
+    action VARCHAR(16) NOT NULL,
+    percentage INTEGER DEFAULT 0 NOT NULL,
+    timesfinished INTEGER DEFAULT 0 NOT NULL,
+    delta INTEGER DEFAULT 0 NOT NULL,
+    created TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
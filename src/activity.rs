@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+
+use chrono::{prelude::*, Duration};
+use tui::{
+    style::Style,
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, List, ListItem, Paragraph},
+};
+
+use finish_it::{History, Item, Topic};
+
+use crate::keymap::{Action, KeyContext, KeyMap};
+use crate::Theme;
+
+const MAX_WEEKS: i64 = 53;
+const DAY_LABELS: [&str; 7] = ["Mon ", "    ", "Wed ", "    ", "Fri ", "    ", "Sun "];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ActivityScope {
+    All,
+    Topic,
+    Item,
+}
+
+impl ActivityScope {
+    pub fn next(self) -> Self {
+        match self {
+            ActivityScope::All => ActivityScope::Topic,
+            ActivityScope::Topic => ActivityScope::Item,
+            ActivityScope::Item => ActivityScope::All,
+        }
+    }
+
+    fn matches(&self, entry: &History, topic: &Topic, item: &Item) -> bool {
+        match self {
+            ActivityScope::All => true,
+            ActivityScope::Topic => entry.topicname == topic.name,
+            ActivityScope::Item => entry.itemid == item.id,
+        }
+    }
+
+    fn title(&self, topic: &Topic, item: &Item) -> String {
+        match self {
+            ActivityScope::All => String::from("Everything"),
+            ActivityScope::Topic => format!("Topic: {}", topic.name),
+            ActivityScope::Item => format!("Item: {}", item.name),
+        }
+    }
+}
+
+/// Moves the selected day by `days`, keeping it inside the last year.
+pub fn move_day(day: NaiveDate, days: i64) -> NaiveDate {
+    move_day_from(Local::now().date_naive(), day, days)
+}
+
+fn move_day_from(today: NaiveDate, day: NaiveDate, days: i64) -> NaiveDate {
+    let first = today - Duration::weeks(MAX_WEEKS);
+    (day + Duration::days(days)).clamp(first, today)
+}
+
+fn local_day(entry: &History) -> NaiveDate {
+    entry.created.with_timezone(&Local).date_naive()
+}
+
+fn cell(level: usize, selected: bool, theme: &Theme) -> Span<'static> {
+    let symbol = match level {
+        0 => "··",
+        1 => "░░",
+        2 => "▒▒",
+        3 => "▓▓",
+        _ => "██",
+    };
+    let style = match selected {
+        true => theme.selection(false).fg(theme.heat(level)),
+        false => Style::default().fg(theme.heat(level)),
+    };
+    Span::styled(symbol, style)
+}
+
+fn level(points: i64, max_points: i64) -> usize {
+    match (points, max_points) {
+        (p, _) if p <= 0 => 0,
+        (_, m) if m <= 0 => 1,
+        (p, m) => 1 + ((p - 1) * 4 / m) as usize,
+    }
+}
+
+/// How to move around the heatmap, with the keys currently bound.
+fn legend_hint(keymap: &KeyMap) -> String {
+    let keys = |action| keymap.describe(action, KeyContext::Activity);
+    format!(
+        "({}, {}, {}, {} select a day, {} changes the filter)",
+        keys(Action::Up),
+        keys(Action::Down),
+        keys(Action::Left),
+        keys(Action::Right),
+        keys(Action::Filter)
+    )
+}
+
+/// Draws the heatmap and the selected day from `history`, which has to reach back a year.
+#[allow(clippy::too_many_arguments)]
+pub fn render_activity<'a>(
+    history: &[History],
+    scope: ActivityScope,
+    topic: &Topic,
+    item: &Item,
+    selected_day: NaiveDate,
+    width: u16,
+    keymap: &KeyMap,
+    theme: &Theme,
+) -> (Paragraph<'a>, List<'a>) {
+    let today = Local::now().date_naive();
+    let weeks = (MAX_WEEKS + 1).min(((width as i64) - 2 - 4) / 2).max(1);
+    let last_monday = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let first_monday = last_monday - Duration::weeks(weeks - 1);
+
+    let since = Local
+        .from_local_datetime(&first_monday.and_hms_opt(0, 0, 0).unwrap_or_default())
+        .earliest()
+        .map(|d| d.with_timezone(&Utc))
+        .unwrap_or_else(Utc::now);
+    let history: Vec<&History> = history
+        .iter()
+        .filter(|entry| entry.created >= since && scope.matches(entry, topic, item))
+        .collect();
+
+    let mut points_per_day: HashMap<NaiveDate, i64> = HashMap::new();
+    for entry in history.iter() {
+        *points_per_day.entry(local_day(entry)).or_insert(0) += entry.delta.max(0);
+    }
+    let max_points = points_per_day.values().copied().max().unwrap_or(0);
+
+    let mut months = String::from("    ");
+    let mut last_month = 0;
+    for week in 0..weeks {
+        let monday = first_monday + Duration::weeks(week);
+        let column = 4 + 2 * week as usize;
+        if monday.month() != last_month && months.len() <= column {
+            last_month = monday.month();
+            months.push_str(&" ".repeat(column - months.len()));
+            months.push_str(&monday.format("%b").to_string());
+        }
+    }
+
+    let mut text = vec![Spans::from(vec![Span::raw(months)])];
+    for (weekday, label) in DAY_LABELS.iter().enumerate() {
+        let mut row = vec![Span::raw(label.to_string())];
+        for week in 0..weeks {
+            let day = first_monday + Duration::weeks(week) + Duration::days(weekday as i64);
+            if day > today {
+                row.push(Span::raw("  "));
+                continue;
+            }
+            let points = points_per_day.get(&day).copied().unwrap_or(0);
+            row.push(cell(level(points, max_points), day == selected_day, theme));
+        }
+        text.push(Spans::from(row));
+    }
+
+    let mut legend = vec![Span::raw("    Less ")];
+    legend.extend((0..5).map(|l| cell(l, false, theme)));
+    legend.push(Span::raw(format!(" More    {}", legend_hint(keymap))));
+    text.push(Spans::from(vec![Span::raw("")]));
+    text.push(Spans::from(legend));
+
+    let heatmap = Paragraph::new(text).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(theme.text())
+            .title(format!("Activity - {}", scope.title(topic, item)))
+            .border_type(BorderType::Plain),
+    );
+
+    let day_entries: Vec<&History> = history
+        .iter()
+        .copied()
+        .filter(|entry| local_day(entry) == selected_day)
+        .collect();
+    let day_points: i64 = day_entries.iter().map(|entry| entry.delta.max(0)).sum();
+
+    let rows: Vec<ListItem> = day_entries
+        .iter()
+        .map(|entry| {
+            ListItem::new(Spans::from(vec![
+                Span::styled(
+                    entry
+                        .created
+                        .with_timezone(&Local)
+                        .format("%H:%M  ")
+                        .to_string(),
+                    theme.muted(),
+                ),
+                Span::styled(format!("{:<7}", entry.action), theme.accent()),
+                Span::raw(format!("{} / {}", entry.topicname, entry.itemname)),
+                Span::styled(
+                    format!(
+                        "  {:+}  ({}%, finished {} times)",
+                        entry.delta, entry.percentage, entry.timesfinished
+                    ),
+                    theme.popup(),
+                ),
+            ]))
+        })
+        .collect();
+
+    let day_list = List::new(rows).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(theme.text())
+            .title(format!(
+                "{} - {} entries, {:+} progress",
+                selected_day.format("%a %Y-%m-%d"),
+                day_entries.len(),
+                day_points
+            ))
+            .border_type(BorderType::Plain),
+    );
+
+    (heatmap, day_list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keymap::{KeysConfig, Preset};
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn levels_split_the_busiest_day_in_four() {
+        assert_eq!(level(0, 0), 0);
+        assert_eq!(level(-5, 100), 0);
+        // Positive points with a broken maximum still show up.
+        assert_eq!(level(3, 0), 1);
+
+        let levels: Vec<usize> = [1, 25, 26, 50, 51, 75, 76, 100]
+            .into_iter()
+            .map(|points| level(points, 100))
+            .collect();
+        assert_eq!(levels, [1, 1, 2, 2, 3, 3, 4, 4]);
+
+        assert_eq!(level(1, 1), 1);
+        assert_eq!(level(2, 3), 2);
+        assert_eq!(level(3, 3), 3);
+        assert_eq!(level(i64::MAX / 8, i64::MAX / 8), 4);
+    }
+
+    #[test]
+    fn days_move_across_weeks_and_years() {
+        let today = date(2027, 3, 10);
+        // Sunday to Monday and back, the next and previous column of the heatmap.
+        assert_eq!(move_day_from(today, date(2027, 2, 28), 1), date(2027, 3, 1));
+        assert_eq!(
+            move_day_from(today, date(2027, 3, 1), -1),
+            date(2027, 2, 28)
+        );
+        assert_eq!(
+            move_day_from(today, date(2027, 3, 3), -7),
+            date(2027, 2, 24)
+        );
+
+        assert_eq!(
+            move_day_from(today, date(2026, 12, 31), 1),
+            date(2027, 1, 1)
+        );
+        assert_eq!(
+            move_day_from(today, date(2027, 1, 3), -7),
+            date(2026, 12, 27)
+        );
+        assert_eq!(
+            move_day_from(date(2028, 3, 10), date(2028, 2, 28), 1),
+            date(2028, 2, 29)
+        );
+    }
+
+    #[test]
+    fn days_stay_within_the_last_year() {
+        let today = date(2027, 1, 2);
+        let first = date(2025, 12, 27);
+        assert_eq!(first, today - Duration::weeks(MAX_WEEKS));
+
+        assert_eq!(move_day_from(today, today, 1), today);
+        assert_eq!(move_day_from(today, date(2026, 12, 30), 7), today);
+        assert_eq!(move_day_from(today, first, -1), first);
+        assert_eq!(move_day_from(today, date(2026, 1, 1), -7), first);
+        assert_eq!(move_day_from(today, first, 7), date(2026, 1, 3));
+    }
+
+    #[test]
+    fn legend_shows_the_bound_keys() {
+        assert_eq!(
+            legend_hint(&KeyMap::default()),
+            "(Up, Down, <-, -> select a day, f changes the filter)"
+        );
+
+        let config = KeysConfig {
+            preset: Preset::Vim,
+            bindings: HashMap::from([(String::from("filter"), vec![String::from("ctrl+f")])]),
+        };
+        let hint = legend_hint(&KeyMap::from_config(&config).unwrap());
+        assert_eq!(
+            hint,
+            "(Up or k, Down or j, <- or h, -> or l select a day, Ctrl+f changes the filter)"
+        );
+    }
+}
@@ -1,15 +1,69 @@
+use std::fmt;
 use std::str::FromStr;
 
 use tui_textarea::{CursorMove, TextArea};
 
 use tui::{
     layout::Alignment,
-    style::{Color, Modifier, Style},
+    style::{Modifier, Style},
     text::{Span, Spans},
     widgets::{Block, BorderType, Borders, Paragraph},
 };
 
-use crate::Confirm;
+use finish_it::Item;
+
+use crate::config::ItemDefaults;
+use crate::keymap::{Action, KeyContext, KeyMap};
+use crate::theme::Theme;
+
+#[derive(PartialEq, Default)]
+pub enum Confirm {
+    #[default]
+    No,
+    Yes,
+}
+
+impl From<Confirm> for usize {
+    fn from(input: Confirm) -> usize {
+        match input {
+            Confirm::No => 0,
+            Confirm::Yes => 1,
+        }
+    }
+}
+
+impl FromStr for Confirm {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Confirm, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "n" | "no" => Ok(Confirm::No),
+            "y" | "yes" => Ok(Confirm::Yes),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Confirm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Confirm::No => write!(f, "N"),
+            Confirm::Yes => write!(f, "Y"),
+        }
+    }
+}
+
+impl Confirm {
+    pub fn get_confirm_str(data: &str) -> String {
+        Confirm::from_str(data).unwrap_or_default().to_string()
+    }
+
+    pub fn get_confirm_u8_str(data: &str) -> String {
+        let integer_str: usize = Confirm::from_str(data).unwrap_or_default().into();
+        integer_str.to_string()
+    }
+}
+
 pub enum AreaType {
     UInt,
     Percentage,
@@ -22,15 +76,17 @@ pub struct TextAreaContainer<'a> {
     pub title: String,
     ok: bool,
     area_type: AreaType,
+    theme: Theme,
 }
 
 impl TextAreaContainer<'_> {
-    pub fn new(title: String, area_type: AreaType) -> Self {
+    pub fn new(title: String, area_type: AreaType, theme: Theme) -> Self {
         Self {
             text_area: TextArea::default(),
             title,
             ok: false,
             area_type,
+            theme,
         }
     }
     pub fn initialize_title(&mut self) {
@@ -43,6 +99,12 @@ impl TextAreaContainer<'_> {
             .set_block(b.style(Style::default()).title(self.title.to_string()));
     }
 
+    pub fn set_text(&mut self, text: &str) {
+        self.clear_text();
+        self.text_area.insert_str(text);
+        self.validate();
+    }
+
     pub fn clear_text(&mut self) {
         self.text_area.move_cursor(CursorMove::Head);
         self.text_area.delete_line_by_end();
@@ -57,8 +119,7 @@ impl TextAreaContainer<'_> {
             .block()
             .cloned()
             .unwrap_or_else(|| Block::default().borders(Borders::ALL));
-        self.text_area
-            .set_block(b.style(Style::default().fg(Color::DarkGray)));
+        self.text_area.set_block(b.style(self.theme.muted()));
     }
 
     pub fn activate(&mut self) {
@@ -75,13 +136,11 @@ impl TextAreaContainer<'_> {
     }
 
     fn set_border_error(&mut self) {
-        self.text_area
-            .set_style(Style::default().fg(Color::LightRed));
+        self.text_area.set_style(self.theme.error());
     }
 
     fn set_border_ok(&mut self) {
-        self.text_area
-            .set_style(Style::default().fg(Color::LightGreen));
+        self.text_area.set_style(self.theme.ok());
     }
 
     pub fn validate(&mut self) {
@@ -154,7 +213,12 @@ impl TextAreaContainer<'_> {
 
     pub fn get_inner_data(&self) -> String {
         let default_str = String::from("0");
-        let inner_data = self.text_area.lines().get(0).unwrap_or(&default_str).trim();
+        let inner_data = self
+            .text_area
+            .lines()
+            .first()
+            .unwrap_or(&default_str)
+            .trim();
 
         match self.area_type {
             AreaType::Confirm => Confirm::get_confirm_u8_str(inner_data),
@@ -163,14 +227,22 @@ impl TextAreaContainer<'_> {
     }
 }
 
-pub fn get_text_areas() -> [TextAreaContainer<'static>; 6] {
+pub fn get_text_areas(defaults: &ItemDefaults, theme: &Theme) -> [TextAreaContainer<'static>; 6] {
     let mut text_areas = [
-        TextAreaContainer::new("Topic Name".to_string(), AreaType::String),
-        TextAreaContainer::new("Item Name".to_string(), AreaType::String),
-        TextAreaContainer::new("Is Recurring? (Yes OR No)".to_string(), AreaType::Confirm),
-        TextAreaContainer::new("% Completed [0, 100]".to_string(), AreaType::Percentage),
-        TextAreaContainer::new("# Completed [0, ...]".to_string(), AreaType::UInt),
-        TextAreaContainer::new("Day Limit [0, ...]".to_string(), AreaType::UInt),
+        TextAreaContainer::new("Topic Name".to_string(), AreaType::String, *theme),
+        TextAreaContainer::new("Item Name".to_string(), AreaType::String, *theme),
+        TextAreaContainer::new(
+            "Is Recurring? (Yes OR No)".to_string(),
+            AreaType::Confirm,
+            *theme,
+        ),
+        TextAreaContainer::new(
+            "% Completed [0, 100]".to_string(),
+            AreaType::Percentage,
+            *theme,
+        ),
+        TextAreaContainer::new("# Completed [0, ...]".to_string(), AreaType::UInt, *theme),
+        TextAreaContainer::new("Day Limit [0, ...]".to_string(), AreaType::UInt, *theme),
     ];
 
     for ta in text_areas.iter_mut() {
@@ -181,10 +253,46 @@ pub fn get_text_areas() -> [TextAreaContainer<'static>; 6] {
     for ta in text_areas.iter_mut().skip(1) {
         ta.inactivate();
     }
+    apply_defaults(&mut text_areas, defaults);
 
     text_areas
 }
 
+/// Fills the recurring and day limit boxes with the configured defaults for new Items.
+pub fn apply_defaults(text_areas: &mut [TextAreaContainer<'static>; 6], defaults: &ItemDefaults) {
+    let recurring = match defaults.recurring {
+        true => Confirm::Yes,
+        false => Confirm::No,
+    };
+    text_areas[2].set_text(&recurring.to_string());
+    text_areas[5].set_text(&defaults.day_limit.to_string());
+}
+
+/// The Item described by the Add form, to be stored with `Repository::add_item`.
+pub fn item_from_text_areas(text_areas: &[TextAreaContainer<'static>; 6]) -> Item {
+    let texts: Vec<String> = text_areas.iter().map(|ta| ta.get_inner_data()).collect();
+    Item {
+        topicname: texts[0].to_owned(),
+        name: texts[1].to_owned(),
+        isrecurring: texts[2].parse().unwrap_or_default(),
+        percentage: texts[3].parse().unwrap_or_default(),
+        timesfinished: texts[4].parse().unwrap_or_default(),
+        daylimit: texts[5].parse().unwrap_or_default(),
+        ..Item::default()
+    }
+}
+
+/// Empties the form after an Item was added and moves the focus back to the first box.
+pub fn clear_text_areas(text_areas: &mut [TextAreaContainer<'static>; 6]) {
+    text_areas.iter_mut().for_each(|ta| {
+        ta.clear_text();
+        ta.inactivate();
+        ta.validate();
+    });
+
+    text_areas[0].activate();
+}
+
 pub fn validate_text_areas(text_areas: &[TextAreaContainer<'static>; 6]) -> bool {
     let ok_sum = text_areas
         .iter()
@@ -196,7 +304,8 @@ pub fn validate_text_areas(text_areas: &[TextAreaContainer<'static>; 6]) -> bool
     ok_sum == text_areas.len() as u8
 }
 
-pub fn get_add_err_text() -> Paragraph<'static> {
+pub fn get_add_err_text(keymap: &KeyMap, theme: &Theme) -> Paragraph<'static> {
+    let confirm = keymap.describe(Action::Confirm, KeyContext::Add);
     let err_text = vec![
         Spans::from(vec![Span::raw("Fill out the form to the left")]),
         Spans::from(vec![Span::raw(
@@ -213,49 +322,56 @@ pub fn get_add_err_text() -> Paragraph<'static> {
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw("Blank inputs are not accepted.")]),
         Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::raw(
-            "Press Enter key when you are done with a box to move on to the next one.",
-        )]),
-        Spans::from(vec![Span::raw("Press Esc key to go back one box")]),
+        Spans::from(vec![Span::raw(format!(
+            "Press {confirm} when you are done with a box to move on to the next one.",
+        ))]),
+        Spans::from(vec![Span::raw(format!(
+            "Press {} to go back one box",
+            keymap.describe(Action::Cancel, KeyContext::Add)
+        ))]),
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw(
             "When everything is green and you are given the ok, ",
         )]),
-        Spans::from(vec![Span::raw(
-            "press Enter key at the last box to add your new item!",
-        )]),
+        Spans::from(vec![Span::raw(format!(
+            "press {confirm} at the last box to add your new item!",
+        ))]),
     ];
-    let err_color = Color::LightRed;
 
     Paragraph::new(err_text).alignment(Alignment::Center).block(
         Block::default()
             .borders(Borders::ALL)
-            .style(Style::default().fg(err_color))
+            .style(theme.error())
             .title("Instructions")
             .border_type(BorderType::Plain),
     )
 }
 
-pub fn get_add_ok_text() -> Paragraph<'static> {
+pub fn get_add_ok_text(keymap: &KeyMap, theme: &Theme) -> Paragraph<'static> {
     let ok_text = vec![
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw("Everything is in order!")]),
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::raw("Confirm your item by pressing the Enter key when the last box is selected.")]),
+        Spans::from(vec![Span::raw(format!(
+            "Confirm your item by pressing {} when the last box is selected.",
+            keymap.describe(Action::Confirm, KeyContext::Add)
+        ))]),
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::raw("You can see your new addition by going back to the Topics screen with Alt+e after adding it.")]),
+        Spans::from(vec![Span::raw(format!(
+            "You can see your new addition by going back to the Topics screen with {} after adding it.",
+            keymap.describe(Action::GoTopics, KeyContext::Add)
+        ))]),
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw("")]),
     ];
-    let ok_color = Color::LightGreen;
 
     Paragraph::new(ok_text).alignment(Alignment::Center).block(
         Block::default()
             .borders(Borders::ALL)
-            .style(Style::default().fg(ok_color))
+            .style(theme.ok())
             .title("Instructions")
             .border_type(BorderType::Plain),
     )
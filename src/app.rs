@@ -0,0 +1,1110 @@
+use chrono::prelude::*;
+use crossterm::event::{KeyEvent, MouseButton, MouseEvent, MouseEventKind};
+use finish_it::{report, Item, Store, StoreError, Topic};
+use tui::widgets::{ListState, TableState};
+
+use crate::activity::{self, ActivityScope};
+use crate::add::{
+    apply_defaults, clear_text_areas, get_text_areas, item_from_text_areas, validate_text_areas,
+    TextAreaContainer,
+};
+use crate::config::Config;
+use crate::keymap::{Action, KeyContext, Resolved};
+use crate::mouse::{Areas, Target};
+use crate::state::AppState;
+use crate::{ActiveBlock, ActivePopUp, MenuItem};
+
+/// What the caller has to do after the App handled an event.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Effect {
+    None,
+    Quit,
+    /// Give the terminal back to the shell until the process is resumed.
+    Suspend,
+    /// Start over with the database of the named profile.
+    SwitchProfile(String),
+}
+
+/// Everything the TUI knows between two events.
+///
+/// Keys and mouse events go through `handle_key` and `handle_mouse`, which turn them into
+/// Actions for `handle_action`. Drawing only reads it, see `ui::draw`.
+pub struct App<'a> {
+    pub store: &'a dyn Store,
+    pub config: &'a Config,
+    pub active_menu_item: MenuItem,
+    pub active_block: ActiveBlock,
+    pub active_popup: ActivePopUp,
+    pub topic_list_state: ListState,
+    pub item_list_state: TableState,
+    pub text_areas: [TextAreaContainer<'static>; 6],
+    pub add_given_ok: bool,
+    /// Text area of the Add tab that has the focus.
+    pub which: usize,
+    /// Keys of a multi-key binding typed so far, e.g. the first `g` of `g g`.
+    pub pending_keys: Vec<KeyEvent>,
+    /// The highlighted Item and Topic, or the ones an open popup works on.
+    pub selected_item: Item,
+    pub selected_topic: Topic,
+    pub review_days: i64,
+    pub review_text: String,
+    pub review_scroll: u16,
+    /// Profiles listed in the switcher popup, read when it opens.
+    pub profiles: Vec<String>,
+    pub profile_list_state: ListState,
+    /// The store forgets everything on exit and belongs to no profile.
+    pub ephemeral: bool,
+    pub activity_scope: ActivityScope,
+    pub activity_day: NaiveDate,
+    /// Where the last frame drew the clickable widgets.
+    pub areas: Areas,
+    /// The help overlay lists the keys of the context it was opened from.
+    pub show_help: bool,
+    pub help_context: KeyContext,
+    /// A failed database call, shown in a popup until dismissed.
+    pub error_message: Option<String>,
+    pub state: AppState,
+}
+
+/// Keeps the TUI running when a database call fails, showing the error in a popup instead.
+fn report<T>(result: Result<T, StoreError>, error_message: &mut Option<String>) -> Option<T> {
+    match result {
+        Ok(value) => Some(value),
+        Err(err) => {
+            *error_message = Some(err.to_string());
+            None
+        }
+    }
+}
+
+/// The Review popup's text, built once when it opens or changes range.
+fn build_review_text(store: &dyn Store, days: i64) -> String {
+    let since = Utc::now() - chrono::Duration::days(days);
+    match report::build_report(store, since) {
+        Ok(report) => report.to_text(),
+        Err(err) => format!("Could not build the review. {err}"),
+    }
+}
+
+impl<'a> App<'a> {
+    pub fn new(store: &'a dyn Store, config: &'a Config) -> Self {
+        let mut topic_list_state = ListState::default();
+        topic_list_state.select(None);
+
+        let mut item_list_state = TableState::default();
+        item_list_state.select(Some(0));
+
+        let mut app = App {
+            store,
+            config,
+            active_menu_item: config.tabs[0],
+            active_block: ActiveBlock::Event,
+            active_popup: ActivePopUp::None,
+            topic_list_state,
+            item_list_state,
+            text_areas: get_text_areas(&config.defaults, &config.palette),
+            add_given_ok: false,
+            which: 0,
+            pending_keys: Vec::new(),
+            selected_item: Item::default(),
+            selected_topic: Topic::default(),
+            review_days: 7,
+            review_text: String::new(),
+            review_scroll: 0,
+            profiles: Vec::new(),
+            profile_list_state: ListState::default(),
+            ephemeral: false,
+            activity_scope: ActivityScope::All,
+            activity_day: Local::now().date_naive(),
+            areas: Areas::default(),
+            show_help: false,
+            help_context: KeyContext::Home,
+            error_message: None,
+            state: AppState::load(store, None),
+        };
+        app.sync();
+        app
+    }
+
+    /// The context keys are looked up in right now.
+    pub fn context(&self) -> KeyContext {
+        KeyContext::from_state(self.active_menu_item, self.active_block, self.active_popup)
+            .with_overlays(self.show_help, self.error_message.is_some())
+    }
+
+    /// Reads everything again if another program or instance changed the database, keeping
+    /// the same Topic and Item selected wherever they moved.
+    pub fn refresh(&mut self) {
+        if !self.state.is_outdated(self.store) {
+            return;
+        }
+        let topic = self.selected_topic.name.clone();
+        let item_id = self.selected_item.id;
+
+        self.state.invalidate();
+        self.state
+            .sync(self.store, self.topic_list_state.selected());
+        if let Some(i) = self.state.topics.iter().position(|t| t.name == topic) {
+            if self.topic_list_state.selected().is_some() || i > 0 {
+                self.topic_list_state.select(Some(i));
+            }
+        }
+        self.state
+            .sync(self.store, self.topic_list_state.selected());
+        if let Some(i) = self.state.items.iter().position(|item| item.id == item_id) {
+            self.item_list_state.select(Some(i));
+        }
+        self.sync();
+    }
+
+    /// Picks up changes in the database and follows the selection, unless a popup is
+    /// working on the selected Item or Topic.
+    ///
+    /// Selections past the end of their list, left behind by deletions, move to its last entry.
+    pub fn sync(&mut self) {
+        self.state
+            .sync(self.store, self.topic_list_state.selected());
+        let topic_count = self.state.topics.len();
+        if self
+            .topic_list_state
+            .selected()
+            .is_some_and(|i| i >= topic_count)
+        {
+            self.topic_list_state.select(topic_count.checked_sub(1));
+            self.state
+                .sync(self.store, self.topic_list_state.selected());
+        }
+        let item_count = self.state.items.len();
+        if self
+            .item_list_state
+            .selected()
+            .is_some_and(|i| i >= item_count)
+        {
+            self.item_list_state
+                .select(Some(item_count.saturating_sub(1)));
+        }
+        if self.active_popup == ActivePopUp::None {
+            if self.active_block == ActiveBlock::InstanceBlock && item_count == 0 {
+                self.active_block = ActiveBlock::Event;
+            }
+            self.selected_topic = self
+                .state
+                .topic(self.topic_list_state.selected())
+                .cloned()
+                .unwrap_or_default();
+            self.selected_item = self
+                .state
+                .item(self.item_list_state.selected())
+                .cloned()
+                .unwrap_or_default();
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) -> Effect {
+        let context = self.context();
+        self.pending_keys.push(key);
+        let action = match self.config.keymap.resolve(&mut self.pending_keys, context) {
+            Resolved::Pending => return Effect::None,
+            Resolved::Action(action) => Some(action),
+            Resolved::Unbound => None,
+        };
+        match action {
+            Some(action) => self.handle_action(action),
+            None => {
+                self.type_key(key);
+                Effect::None
+            }
+        }
+    }
+
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) -> Effect {
+        if self.show_help || self.error_message.is_some() {
+            return Effect::None;
+        }
+        let target = self.areas.target(mouse.column, mouse.row);
+        let item_count = self.state.items.len();
+        let action = match (mouse.kind, target, self.active_popup) {
+            (MouseEventKind::ScrollUp, _, ActivePopUp::Review) => Some(Action::Up),
+            (MouseEventKind::ScrollDown, _, ActivePopUp::Review) => Some(Action::Down),
+            (_, _, popup) if popup != ActivePopUp::None => None,
+
+            (MouseEventKind::Down(MouseButton::Left), Some(Target::Tab(i)), _) => Some(
+                self.config
+                    .tabs
+                    .get(i)
+                    .map_or(Action::Quit, |tab| Action::go_to(*tab)),
+            ),
+
+            (MouseEventKind::Down(MouseButton::Left), Some(Target::Topic(i)), _) => {
+                if i < self.state.topics.len() {
+                    if self.topic_list_state.selected() != Some(i) {
+                        self.item_list_state.select(Some(0));
+                    }
+                    self.topic_list_state.select(Some(i));
+                    self.active_block = ActiveBlock::Event;
+                }
+                None
+            }
+
+            // A double click opens the Update popup like Enter does.
+            (MouseEventKind::Down(MouseButton::Left), Some(Target::Item(i)), _)
+                if i < item_count =>
+            {
+                self.active_block = ActiveBlock::InstanceBlock;
+                self.item_list_state.select(Some(i));
+                match self.areas.click(Target::Item(i)) {
+                    true => Some(Action::Update),
+                    false => None,
+                }
+            }
+
+            (MouseEventKind::Down(MouseButton::Left), Some(Target::TextArea(i)), _) => {
+                self.text_areas[self.which].inactivate();
+                self.which = i;
+                self.text_areas[self.which].activate();
+                None
+            }
+
+            (MouseEventKind::ScrollUp, Some(Target::Topic(_)), _) => {
+                self.active_block = ActiveBlock::Event;
+                Some(Action::Up)
+            }
+
+            (MouseEventKind::ScrollDown, Some(Target::Topic(_)), _) => {
+                self.active_block = ActiveBlock::Event;
+                Some(Action::Down)
+            }
+
+            (MouseEventKind::ScrollUp, Some(Target::Item(_)), _) if item_count > 0 => {
+                self.active_block = ActiveBlock::InstanceBlock;
+                Some(Action::Up)
+            }
+
+            (MouseEventKind::ScrollDown, Some(Target::Item(_)), _) if item_count > 0 => {
+                self.active_block = ActiveBlock::InstanceBlock;
+                Some(Action::Down)
+            }
+
+            _ => None,
+        };
+        match action {
+            Some(action) => self.handle_action(action),
+            None => {
+                self.sync();
+                Effect::None
+            }
+        }
+    }
+
+    pub fn handle_action(&mut self, action: Action) -> Effect {
+        let effect = self.dispatch(action);
+        self.sync();
+        effect
+    }
+
+    /// Types a key no binding wanted into the focused text area of the Add tab.
+    fn type_key(&mut self, key: KeyEvent) {
+        if self.context() != KeyContext::Add {
+            return;
+        }
+        if self.text_areas[self.which].text_area.input(key) {
+            self.text_areas[self.which].validate();
+        }
+        self.add_given_ok = validate_text_areas(&self.text_areas);
+    }
+
+    fn dispatch(&mut self, action: Action) -> Effect {
+        let item_count = self.state.items.len();
+        match (
+            action,
+            self.active_menu_item,
+            self.active_block,
+            self.active_popup,
+        ) {
+            // Global Keys
+            (Action::Quit, _, _, _) => return Effect::Quit,
+
+            (Action::Suspend, _, _, _) => return Effect::Suspend,
+
+            // Error Popup
+            (Action::Confirm | Action::Cancel, _, _, _) if self.error_message.is_some() => {
+                self.error_message = None
+            }
+            _ if self.error_message.is_some() => {}
+
+            // Help Overlay
+            (Action::Help | Action::Cancel, _, _, _) if self.show_help => self.show_help = false,
+            _ if self.show_help => {}
+
+            (Action::Help, menu, block, popup) => {
+                self.help_context = KeyContext::from_state(menu, block, popup);
+                self.show_help = true;
+            }
+
+            (Action::GoHome, _, _, ActivePopUp::None)
+                if self.config.tabs.contains(&MenuItem::Home) =>
+            {
+                self.active_menu_item = MenuItem::Home
+            }
+
+            (Action::GoTopics, _, _, ActivePopUp::None)
+                if self.config.tabs.contains(&MenuItem::Instances) =>
+            {
+                self.active_menu_item = MenuItem::Instances;
+                self.state.invalidate();
+            }
+
+            (Action::GoAdd, _, _, ActivePopUp::None)
+                if self.config.tabs.contains(&MenuItem::Add) =>
+            {
+                self.active_menu_item = MenuItem::Add
+            }
+
+            (Action::GoActivity, _, _, ActivePopUp::None)
+                if self.config.tabs.contains(&MenuItem::Activity) =>
+            {
+                self.active_menu_item = MenuItem::Activity;
+                self.state.invalidate();
+            }
+
+            (Action::Review, _, _, ActivePopUp::None) => {
+                self.review_text = build_review_text(self.store, self.review_days);
+                self.review_scroll = 0;
+                self.active_popup = ActivePopUp::Review;
+            }
+
+            (Action::Profiles, _, _, ActivePopUp::None) if self.ephemeral => {
+                self.error_message = Some(String::from(
+                    "An --ephemeral session belongs to no profile and can not switch to one.",
+                ))
+            }
+
+            (Action::Profiles, _, _, ActivePopUp::None) => {
+                self.profiles = self.config.profile_names();
+                let current = self
+                    .profiles
+                    .iter()
+                    .position(|name| *name == self.config.profile);
+                self.profile_list_state.select(current.or(Some(0)));
+                self.active_popup = ActivePopUp::Profiles;
+            }
+
+            // Profile Popup
+            (Action::Up, _, _, ActivePopUp::Profiles) => {
+                let selected = self.profile_list_state.selected().unwrap_or(0);
+                self.profile_list_state.select(Some(match selected {
+                    0 => self.profiles.len().saturating_sub(1),
+                    n => n - 1,
+                }));
+            }
+
+            (Action::Down, _, _, ActivePopUp::Profiles) => {
+                let selected = self.profile_list_state.selected().unwrap_or(0);
+                self.profile_list_state.select(Some(match selected + 1 {
+                    n if n >= self.profiles.len() => 0,
+                    n => n,
+                }));
+            }
+
+            (Action::Confirm, _, _, ActivePopUp::Profiles) => {
+                self.active_popup = ActivePopUp::None;
+                let selected = self.profile_list_state.selected();
+                if let Some(name) = selected.and_then(|i| self.profiles.get(i)) {
+                    if *name != self.config.profile {
+                        return Effect::SwitchProfile(name.clone());
+                    }
+                }
+            }
+
+            (Action::Cancel, _, _, ActivePopUp::Profiles) => self.active_popup = ActivePopUp::None,
+
+            // Review Popup
+            (Action::Cancel, _, _, ActivePopUp::Review) => self.active_popup = ActivePopUp::None,
+
+            (Action::Range, _, _, ActivePopUp::Review) => {
+                self.review_days = if self.review_days == 7 { 30 } else { 7 };
+                self.review_text = build_review_text(self.store, self.review_days);
+                self.review_scroll = 0;
+            }
+
+            (Action::Up, _, _, ActivePopUp::Review) => {
+                self.review_scroll = self.review_scroll.saturating_sub(1)
+            }
+
+            (Action::Down, _, _, ActivePopUp::Review) => {
+                self.review_scroll = self.review_scroll.saturating_add(1)
+            }
+
+            // Instances - Event Block Keys
+            (Action::Down, MenuItem::Instances, ActiveBlock::Event, ActivePopUp::None) => {
+                let e = &self.state.topics;
+                if !e.is_empty() {
+                    let selected = self.topic_list_state.selected().unwrap_or(0);
+                    if selected >= e.len() - 1 {
+                        self.topic_list_state.select(Some(0));
+                    } else {
+                        self.topic_list_state.select(Some(selected + 1));
+                    }
+                }
+            }
+
+            (Action::Up, MenuItem::Instances, ActiveBlock::Event, ActivePopUp::None) => {
+                let e = &self.state.topics;
+                if !e.is_empty() {
+                    let selected = self.topic_list_state.selected().unwrap_or(0);
+                    if selected > 0 {
+                        self.topic_list_state.select(Some(selected - 1));
+                    } else {
+                        self.topic_list_state.select(Some(e.len() - 1));
+                    }
+                }
+            }
+
+            (Action::First, MenuItem::Instances, ActiveBlock::Event, ActivePopUp::None)
+                if !self.state.topics.is_empty() =>
+            {
+                self.topic_list_state.select(Some(0))
+            }
+
+            (Action::Last, MenuItem::Instances, ActiveBlock::Event, ActivePopUp::None)
+                if !self.state.topics.is_empty() =>
+            {
+                self.topic_list_state
+                    .select(Some(self.state.topics.len() - 1))
+            }
+
+            (Action::Right, MenuItem::Instances, ActiveBlock::Event, ActivePopUp::None)
+                if item_count > 0 =>
+            {
+                self.active_block = ActiveBlock::InstanceBlock;
+                self.item_list_state.select(Some(0));
+            }
+
+            (Action::Delete, MenuItem::Instances, ActiveBlock::Event, ActivePopUp::None) => {
+                self.active_popup = ActivePopUp::Delete;
+            }
+
+            (Action::Confirm, MenuItem::Instances, ActiveBlock::Event, ActivePopUp::Delete) => {
+                if report(
+                    self.store.delete_topic(&self.selected_topic),
+                    &mut self.error_message,
+                )
+                .is_none()
+                {
+                    return Effect::None;
+                }
+                self.active_popup = ActivePopUp::None;
+                if report(
+                    self.state
+                        .reload(self.store, self.topic_list_state.selected()),
+                    &mut self.error_message,
+                )
+                .is_some()
+                {
+                    let e = &self.state.topics;
+                    if e.is_empty() {
+                        self.topic_list_state.select(None);
+                    } else if let Some(selected) = self.topic_list_state.selected() {
+                        if selected >= e.len() {
+                            self.topic_list_state.select(Some(e.len() - 1));
+                        }
+                    }
+                }
+            }
+
+            // Instances - Instance Block Keys
+            (Action::Down, MenuItem::Instances, ActiveBlock::InstanceBlock, ActivePopUp::None) => {
+                if let Some(selected) = self.item_list_state.selected() {
+                    if item_count > 0 {
+                        if selected >= item_count - 1 {
+                            self.item_list_state.select(Some(0));
+                        } else {
+                            self.item_list_state.select(Some(selected + 1));
+                        }
+                    }
+                }
+            }
+
+            (Action::Up, MenuItem::Instances, ActiveBlock::InstanceBlock, ActivePopUp::None) => {
+                if let Some(selected) = self.item_list_state.selected() {
+                    if item_count > 0 {
+                        if selected > 0 {
+                            self.item_list_state.select(Some(selected - 1));
+                        } else {
+                            self.item_list_state.select(Some(item_count - 1));
+                        }
+                    }
+                }
+            }
+
+            (Action::First, MenuItem::Instances, ActiveBlock::InstanceBlock, ActivePopUp::None)
+                if item_count > 0 =>
+            {
+                self.item_list_state.select(Some(0))
+            }
+
+            (Action::Last, MenuItem::Instances, ActiveBlock::InstanceBlock, ActivePopUp::None)
+                if item_count > 0 =>
+            {
+                self.item_list_state.select(Some(item_count - 1))
+            }
+
+            (Action::Left, MenuItem::Instances, ActiveBlock::InstanceBlock, ActivePopUp::None) => {
+                self.active_block = ActiveBlock::Event;
+                let selected = self.topic_list_state.selected().unwrap_or(0);
+                self.topic_list_state.select(Some(selected));
+            }
+
+            (
+                Action::Update,
+                MenuItem::Instances,
+                ActiveBlock::InstanceBlock,
+                ActivePopUp::None,
+            ) => {
+                self.active_popup = ActivePopUp::Update;
+            }
+
+            (
+                Action::Delete,
+                MenuItem::Instances,
+                ActiveBlock::InstanceBlock,
+                ActivePopUp::None,
+            ) => {
+                self.active_popup = ActivePopUp::Delete;
+            }
+
+            // Update Popup
+            (
+                Action::Increment,
+                MenuItem::Instances,
+                ActiveBlock::InstanceBlock,
+                ActivePopUp::Update,
+            ) => {
+                self.selected_item.increment_one();
+            }
+
+            (
+                Action::Decrement,
+                MenuItem::Instances,
+                ActiveBlock::InstanceBlock,
+                ActivePopUp::Update,
+            ) => {
+                self.selected_item.decrement_one();
+            }
+
+            (
+                Action::FinishOnce,
+                MenuItem::Instances,
+                ActiveBlock::InstanceBlock,
+                ActivePopUp::Update,
+            ) => {
+                self.selected_item.finish_once();
+            }
+
+            (
+                Action::Confirm,
+                MenuItem::Instances,
+                ActiveBlock::InstanceBlock,
+                ActivePopUp::Update,
+            ) => {
+                if report(
+                    self.store.update_item(&self.selected_item),
+                    &mut self.error_message,
+                )
+                .is_none()
+                {
+                    return Effect::None;
+                }
+                self.active_popup = ActivePopUp::None;
+                report(
+                    self.state
+                        .reload(self.store, self.topic_list_state.selected()),
+                    &mut self.error_message,
+                );
+            }
+
+            // Delete Popup
+            (
+                Action::Confirm,
+                MenuItem::Instances,
+                ActiveBlock::InstanceBlock,
+                ActivePopUp::Delete,
+            ) => {
+                if report(
+                    self.store.delete_item(&self.selected_item),
+                    &mut self.error_message,
+                )
+                .is_none()
+                {
+                    return Effect::None;
+                }
+                self.active_popup = ActivePopUp::None;
+                if report(
+                    self.state
+                        .reload(self.store, self.topic_list_state.selected()),
+                    &mut self.error_message,
+                )
+                .is_some()
+                {
+                    let item_count = self.state.items.len();
+                    if item_count == 0 {
+                        self.active_block = ActiveBlock::Event;
+                    } else if let Some(selected) = self.item_list_state.selected() {
+                        if selected >= item_count {
+                            self.item_list_state.select(Some(item_count - 1));
+                        }
+                    }
+                }
+            }
+
+            // Instances - For Both Popups
+            (Action::Cancel, MenuItem::Instances, _, ActivePopUp::Update | ActivePopUp::Delete) => {
+                self.active_popup = ActivePopUp::None;
+            }
+
+            // Add Tab Keys
+            (Action::Confirm, MenuItem::Add, _, ActivePopUp::None) => {
+                if self.which + 1 >= self.text_areas.len() {
+                    if self.add_given_ok {
+                        // The form keeps its text when the insert fails, to try again.
+                        if report(
+                            self.store.add_item(&item_from_text_areas(&self.text_areas)),
+                            &mut self.error_message,
+                        )
+                        .is_some()
+                        {
+                            clear_text_areas(&mut self.text_areas);
+                            report(
+                                self.state
+                                    .reload(self.store, self.topic_list_state.selected()),
+                                &mut self.error_message,
+                            );
+                            apply_defaults(&mut self.text_areas, &self.config.defaults);
+                            self.add_given_ok = validate_text_areas(&self.text_areas);
+                            self.which = 0;
+                        }
+                    }
+                } else {
+                    self.text_areas[self.which].inactivate();
+                    self.which += 1;
+                    self.text_areas[self.which].activate();
+                }
+            }
+
+            (Action::Cancel, MenuItem::Add, _, ActivePopUp::None) => {
+                self.text_areas[self.which].inactivate();
+                self.which = self.which.saturating_sub(1);
+                self.text_areas[self.which].activate();
+            }
+
+            // Activity Tab Keys
+            (Action::Left, MenuItem::Activity, _, ActivePopUp::None) => {
+                self.activity_day = activity::move_day(self.activity_day, -7)
+            }
+
+            (Action::Right, MenuItem::Activity, _, ActivePopUp::None) => {
+                self.activity_day = activity::move_day(self.activity_day, 7)
+            }
+
+            (Action::Up, MenuItem::Activity, _, ActivePopUp::None) => {
+                self.activity_day = activity::move_day(self.activity_day, -1)
+            }
+
+            (Action::Down, MenuItem::Activity, _, ActivePopUp::None) => {
+                self.activity_day = activity::move_day(self.activity_day, 1)
+            }
+
+            (Action::Filter, MenuItem::Activity, _, ActivePopUp::None) => {
+                self.activity_scope = self.activity_scope.next()
+            }
+
+            _ => {}
+        }
+        Effect::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::{KeyCode, KeyModifiers};
+    use tui::{backend::TestBackend, Terminal};
+
+    use finish_it::{MemoryStore, Repository};
+
+    use super::*;
+    use crate::ui;
+
+    /// Fills `store` with two Topics and three Items.
+    fn seed<S: Store>(store: S) -> S {
+        let items: Vec<Item> = [
+            ("Books", "Read SICP", 40),
+            ("Books", "Read TAOCP", 0),
+            ("Garden", "Plant tomatoes", 90),
+        ]
+        .into_iter()
+        .map(|(topic, name, percentage)| Item {
+            name: name.to_string(),
+            topicname: topic.to_string(),
+            percentage,
+            created: Utc::now(),
+            ..Item::default()
+        })
+        .collect();
+        store.import_items(&items).unwrap();
+        store
+    }
+
+    fn test_db() -> MemoryStore {
+        seed(MemoryStore::new())
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn alt(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT)
+    }
+
+    fn press(app: &mut App, keys: &[KeyEvent]) -> Effect {
+        keys.iter()
+            .map(|key| app.handle_key(*key))
+            .last()
+            .unwrap_or(Effect::None)
+    }
+
+    fn type_text(app: &mut App, text: &str) {
+        for c in text.chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+    }
+
+    /// Draws `app` like the TUI does and returns the screen as lines of text.
+    fn screen(app: &mut App, width: u16, height: u16) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        let mut areas = Areas::default();
+        terminal.draw(|rect| areas = ui::draw(app, rect)).unwrap();
+        app.areas = areas;
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buffer.get(x, y).symbol.as_str())
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn percentage_in_db(store: &dyn Store, name: &str) -> u8 {
+        let items = store.all_items().unwrap();
+        items
+            .iter()
+            .find(|item| item.name == name)
+            .unwrap()
+            .percentage
+    }
+
+    #[test]
+    fn navigates_topics_and_items() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+
+        press(&mut app, &[alt('t')]);
+        assert_eq!(app.active_menu_item, MenuItem::Instances);
+        assert_eq!(app.selected_topic.name, "Books");
+
+        press(&mut app, &[key(KeyCode::Down), key(KeyCode::Right)]);
+        assert_eq!(app.selected_topic.name, "Garden");
+        assert_eq!(app.active_block, ActiveBlock::InstanceBlock);
+        assert_eq!(app.selected_item.name, "Plant tomatoes");
+
+        press(&mut app, &[key(KeyCode::Left), key(KeyCode::Down)]);
+        assert_eq!(app.active_block, ActiveBlock::Event);
+        assert_eq!(app.selected_topic.name, "Books");
+        assert_eq!(app.state.items.len(), 2);
+        assert!(app
+            .state
+            .items
+            .iter()
+            .all(|item| app.state.forecasts.contains_key(&item.id)));
+
+        let text = screen(&mut app, 120, 30);
+        assert!(text.contains("Read SICP"));
+        assert!(text.contains("Read TAOCP"));
+        assert!(!text.contains("Plant tomatoes"));
+    }
+
+    #[test]
+    fn updates_progress_through_the_popup() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+
+        press(
+            &mut app,
+            &[alt('t'), key(KeyCode::Right), key(KeyCode::Enter)],
+        );
+        assert_eq!(app.active_popup, ActivePopUp::Update);
+        assert!(screen(&mut app, 120, 30).contains("Update Progress"));
+
+        press(&mut app, &[key(KeyCode::Right), key(KeyCode::Right)]);
+        assert_eq!(app.selected_item.percentage, 42);
+        assert_eq!(percentage_in_db(&store, "Read SICP"), 40);
+
+        press(&mut app, &[key(KeyCode::Enter)]);
+        assert_eq!(app.active_popup, ActivePopUp::None);
+        assert_eq!(percentage_in_db(&store, "Read SICP"), 42);
+        assert_eq!(app.state.items[0].percentage, 42);
+    }
+
+    #[test]
+    fn cancelling_the_popup_keeps_the_database_unchanged() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+
+        press(
+            &mut app,
+            &[
+                alt('t'),
+                key(KeyCode::Right),
+                key(KeyCode::Enter),
+                key(KeyCode::Left),
+                key(KeyCode::Esc),
+            ],
+        );
+        assert_eq!(app.active_popup, ActivePopUp::None);
+        assert_eq!(app.selected_item.percentage, 40);
+        assert_eq!(percentage_in_db(&store, "Read SICP"), 40);
+    }
+
+    #[test]
+    fn deleting_the_last_topic_moves_the_selection_up() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+
+        press(
+            &mut app,
+            &[
+                alt('t'),
+                key(KeyCode::Down),
+                key(KeyCode::Delete),
+                key(KeyCode::Enter),
+            ],
+        );
+        assert_eq!(app.state.topics.len(), 1);
+        assert_eq!(app.topic_list_state.selected(), Some(0));
+        assert_eq!(app.selected_topic.name, "Books");
+        assert!(!screen(&mut app, 120, 30).contains("Garden"));
+    }
+
+    #[test]
+    fn adds_an_item_from_the_form() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+
+        press(&mut app, &[alt('a')]);
+        type_text(&mut app, "Garden");
+        press(&mut app, &[key(KeyCode::Enter)]);
+        type_text(&mut app, "Water plants");
+        press(&mut app, &[key(KeyCode::Enter), key(KeyCode::Enter)]);
+        type_text(&mut app, "10");
+        press(&mut app, &[key(KeyCode::Enter)]);
+        type_text(&mut app, "0");
+        press(&mut app, &[key(KeyCode::Enter)]);
+        assert!(app.add_given_ok);
+
+        press(&mut app, &[key(KeyCode::Enter)]);
+        assert_eq!(app.error_message, None);
+        assert_eq!(app.which, 0);
+        assert_eq!(percentage_in_db(&store, "Water plants"), 10);
+
+        press(&mut app, &[alt('t'), key(KeyCode::Down)]);
+        assert_eq!(app.state.items.len(), 2);
+    }
+
+    #[test]
+    fn typing_q_in_the_add_form_does_not_quit() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+
+        press(&mut app, &[alt('a')]);
+        type_text(&mut app, "q");
+        assert_eq!(app.text_areas[0].get_inner_data(), "q");
+        assert_eq!(press(&mut app, &[alt('q')]), Effect::Quit);
+    }
+
+    #[test]
+    fn database_errors_open_a_popup() {
+        let repo = seed(Repository::open_in_memory().unwrap());
+        let config = Config::default();
+        let mut app = App::new(&repo, &config);
+
+        press(
+            &mut app,
+            &[alt('t'), key(KeyCode::Right), key(KeyCode::Enter)],
+        );
+        repo.connection().execute("DROP TABLE history", []).unwrap();
+        press(&mut app, &[key(KeyCode::Enter)]);
+        let message = app.error_message.clone().unwrap();
+        assert!(message.contains("history"), "{message}");
+        assert!(screen(&mut app, 120, 30).contains("Error"));
+
+        // Keys go to the popup until it is dismissed.
+        press(&mut app, &[key(KeyCode::Down), key(KeyCode::Enter)]);
+        assert_eq!(app.error_message, None);
+    }
+
+    #[test]
+    fn help_lists_the_keys_of_the_current_tab() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+
+        press(&mut app, &[alt('t'), key(KeyCode::Char('?'))]);
+        assert!(app.show_help);
+        assert_eq!(app.help_context, KeyContext::Topics);
+        assert!(screen(&mut app, 120, 40).contains("Keys - Topics"));
+
+        press(&mut app, &[key(KeyCode::Esc)]);
+        assert!(!app.show_help);
+    }
+
+    #[test]
+    fn narrow_terminals_get_the_compact_layout() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+        press(&mut app, &[alt('t')]);
+
+        let wide = screen(&mut app, 200, 30);
+        assert!(wide.contains("Created At"));
+        let narrow = screen(&mut app, 80, 30);
+        assert!(narrow.contains("Read SICP"));
+        assert!(!narrow.contains("Created At"));
+        let tiny = screen(&mut app, 30, 10);
+        assert!(tiny.contains("Terminal too small"));
+    }
+
+    #[test]
+    fn double_clicking_an_item_opens_the_update_popup() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+        press(&mut app, &[alt('t')]);
+        screen(&mut app, 120, 30);
+
+        // Second row of the Items table, under its border and header.
+        let items = app.areas.items;
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: items.x + 5,
+            row: items.y + 3,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.handle_mouse(click);
+        assert_eq!(app.active_block, ActiveBlock::InstanceBlock);
+        assert_eq!(app.selected_item.name, "Read TAOCP");
+        assert_eq!(app.active_popup, ActivePopUp::None);
+
+        app.handle_mouse(click);
+        assert_eq!(app.active_popup, ActivePopUp::Update);
+    }
+
+    #[test]
+    fn changes_from_other_instances_keep_the_selection() {
+        let path = std::env::temp_dir().join(format!("finish-it-reload-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let repo = seed(Repository::open(&path).unwrap());
+        let other = Repository::open(&path).unwrap();
+        let config = Config::default();
+        let mut app = App::new(&repo, &config);
+        press(
+            &mut app,
+            &[alt('t'), key(KeyCode::Right), key(KeyCode::Down)],
+        );
+        assert_eq!(app.selected_item.name, "Read TAOCP");
+
+        // Nothing changed yet, so nothing is read again.
+        app.refresh();
+        assert_eq!(app.item_list_state.selected(), Some(1));
+
+        let books = other.items("Books").unwrap();
+        other.delete_item(&books[0]).unwrap();
+        app.refresh();
+        assert_eq!(app.state.items.len(), 1);
+        assert_eq!(app.item_list_state.selected(), Some(0));
+        assert_eq!(app.selected_item.name, "Read TAOCP");
+
+        press(&mut app, &[key(KeyCode::Left), key(KeyCode::Down)]);
+        assert_eq!(app.selected_topic.name, "Garden");
+        let books = other.topics().unwrap().remove(0);
+        other.delete_topic(&books).unwrap();
+        app.refresh();
+        assert_eq!(app.topic_list_state.selected(), Some(0));
+        assert_eq!(app.selected_topic.name, "Garden");
+        assert_eq!(app.state.items[0].name, "Plant tomatoes");
+
+        // Deleting what is selected moves the selection back onto the list.
+        press(&mut app, &[key(KeyCode::Right)]);
+        let garden = other.topics().unwrap().remove(0);
+        other.delete_topic(&garden).unwrap();
+        app.refresh();
+        assert_eq!(app.topic_list_state.selected(), None);
+        assert_eq!(app.active_block, ActiveBlock::Event);
+        assert!(!screen(&mut app, 120, 30).contains("Plant tomatoes"));
+
+        drop(app);
+        drop((repo, other));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn switching_profiles_is_left_to_the_caller() {
+        let store = test_db();
+        let mut config = Config::default();
+        config
+            .profiles
+            .insert(String::from("work"), "work.db".into());
+        let mut app = App::new(&store, &config);
+
+        press(&mut app, &[alt('p')]);
+        assert_eq!(app.active_popup, ActivePopUp::Profiles);
+        assert_eq!(app.profile_list_state.selected(), Some(0));
+        let text = screen(&mut app, 120, 30);
+        assert!(text.contains("Menu - default"));
+        assert!(text.contains("default (active)"));
+
+        // Picking the active profile only closes the popup.
+        assert_eq!(press(&mut app, &[key(KeyCode::Enter)]), Effect::None);
+        assert_eq!(app.active_popup, ActivePopUp::None);
+        assert_eq!(
+            press(
+                &mut app,
+                &[alt('p'), key(KeyCode::Down), key(KeyCode::Enter)]
+            ),
+            Effect::SwitchProfile(String::from("work"))
+        );
+
+        app.ephemeral = true;
+        press(&mut app, &[alt('p')]);
+        assert_eq!(app.active_popup, ActivePopUp::None);
+        assert!(app.error_message.is_some());
+    }
+
+    #[test]
+    fn signals_and_suspend_are_left_to_the_caller() {
+        let store = test_db();
+        let config = Config::default();
+        let mut app = App::new(&store, &config);
+
+        let ctrl_z = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        assert_eq!(press(&mut app, &[ctrl_z]), Effect::Suspend);
+        assert_eq!(app.handle_action(Action::Quit), Effect::Quit);
+    }
+}
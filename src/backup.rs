@@ -0,0 +1,260 @@
+//! Snapshots of the database, taken with SQLite's online backup API while it is in use.
+
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime, Timelike};
+use rusqlite::DatabaseName;
+
+use crate::model::Item;
+use crate::repository::Repository;
+
+/// Snapshot names are the database's file stem followed by when they were taken.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d-%H%M%S";
+
+/// Why a snapshot could not be taken, listed or restored.
+#[derive(Debug)]
+pub enum BackupError {
+    /// Reading or changing the backups directory failed.
+    Io(PathBuf, std::io::Error),
+    /// SQLite failed to copy the database.
+    Sqlite(rusqlite::Error),
+    /// The database lives in memory and has no file to back up or restore.
+    InMemory,
+}
+
+impl fmt::Display for BackupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BackupError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            BackupError::Sqlite(err) => write!(f, "{}", crate::describe_error(err)),
+            BackupError::InMemory => write!(f, "the database lives in memory, it has no backups"),
+        }
+    }
+}
+
+impl std::error::Error for BackupError {}
+
+impl From<rusqlite::Error> for BackupError {
+    fn from(err: rusqlite::Error) -> Self {
+        BackupError::Sqlite(err)
+    }
+}
+
+/// A copy of the database in the backups directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    /// Where the copy is.
+    pub path: PathBuf,
+    /// When it was taken, in local time.
+    pub taken: NaiveDateTime,
+}
+
+/// The file stem snapshots of `repo` start with.
+fn stem(repo: &Repository) -> Result<String, BackupError> {
+    let path = repo.path().ok_or(BackupError::InMemory)?;
+    Ok(path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| String::from("fit")))
+}
+
+/// Snapshots of `repo` in `dir`, oldest first. Other files in `dir` are left out.
+pub fn list(repo: &Repository, dir: &Path) -> Result<Vec<Snapshot>, BackupError> {
+    let prefix = format!("{}-", stem(repo)?);
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(BackupError::Io(dir.into(), err)),
+    };
+
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|err| BackupError::Io(dir.into(), err))?
+            .path();
+        let taken = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix(&prefix)?.strip_suffix(".db"))
+            .and_then(|time| NaiveDateTime::parse_from_str(time, TIMESTAMP_FORMAT).ok());
+        if let Some(taken) = taken {
+            snapshots.push(Snapshot { path, taken });
+        }
+    }
+    snapshots.sort_by_key(|snapshot| snapshot.taken);
+    Ok(snapshots)
+}
+
+/// Copies `repo` into `dir` now, creating `dir` if needed.
+pub fn take(repo: &Repository, dir: &Path) -> Result<Snapshot, BackupError> {
+    std::fs::create_dir_all(dir).map_err(|err| BackupError::Io(dir.into(), err))?;
+    let stem = stem(repo)?;
+    // Only seconds make it into the file name. Snapshots taken within the same one are a second
+    // apart, so none overwrites another.
+    let mut taken = Local::now()
+        .naive_local()
+        .with_nanosecond(0)
+        .unwrap_or_default();
+    let mut path = dir.join(format!("{}-{}.db", stem, taken.format(TIMESTAMP_FORMAT)));
+    while path.exists() {
+        taken += chrono::Duration::seconds(1);
+        path = dir.join(format!("{}-{}.db", stem, taken.format(TIMESTAMP_FORMAT)));
+    }
+    repo.connection().backup(DatabaseName::Main, &path, None)?;
+    Ok(Snapshot { path, taken })
+}
+
+/// Deletes the oldest snapshots of `repo` until `keep` are left and returns the deleted ones.
+pub fn prune(repo: &Repository, dir: &Path, keep: usize) -> Result<Vec<Snapshot>, BackupError> {
+    let mut snapshots = list(repo, dir)?;
+    let excess = snapshots.len().saturating_sub(keep);
+    let removed: Vec<Snapshot> = snapshots.drain(..excess).collect();
+    for snapshot in removed.iter() {
+        std::fs::remove_file(&snapshot.path)
+            .map_err(|err| BackupError::Io(snapshot.path.clone(), err))?;
+    }
+    Ok(removed)
+}
+
+/// Takes a snapshot unless one was taken today already, then keeps the last `keep`.
+/// Does nothing if `keep` is 0.
+pub fn daily(repo: &Repository, dir: &Path, keep: usize) -> Result<Option<Snapshot>, BackupError> {
+    if keep == 0 {
+        return Ok(None);
+    }
+    let today = Local::now().date_naive();
+    let snapshot = match list(repo, dir)?.iter().any(|s| s.taken.date() == today) {
+        true => None,
+        false => Some(take(repo, dir)?),
+    };
+    prune(repo, dir, keep)?;
+    Ok(snapshot)
+}
+
+/// Replaces `repo` with the snapshot at `path`, after taking a snapshot of it into `dir` so the
+/// restore can be undone. Returns that snapshot and the Items upgrading the restored database
+/// removed, see [`Repository::restore_from`].
+pub fn restore(
+    repo: &Repository,
+    dir: &Path,
+    path: &Path,
+) -> Result<(Snapshot, Vec<Item>), BackupError> {
+    if !path.is_file() {
+        return Err(BackupError::Io(
+            path.into(),
+            std::io::Error::new(std::io::ErrorKind::NotFound, "no such snapshot"),
+        ));
+    }
+    let before = take(repo, dir)?;
+    let removed = repo.restore_from(path)?;
+    Ok((before, removed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+
+    #[test]
+    fn takes_prunes_and_restores_snapshots() {
+        let dir = std::env::temp_dir().join(format!("finish-it-backup-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let repo = Repository::open(&dir.join("fit.db")).unwrap();
+        let backups = dir.join("backups");
+        let item = Item {
+            name: String::from("Read SICP"),
+            topicname: String::from("Books"),
+            ..Item::default()
+        };
+        repo.add_item(&item).unwrap();
+
+        let first = daily(&repo, &backups, 2).unwrap().unwrap();
+        assert_eq!(daily(&repo, &backups, 2).unwrap(), None);
+        std::fs::write(backups.join("notes.txt"), "not a snapshot").unwrap();
+
+        let topic = repo.topics().unwrap().remove(0);
+        repo.delete_topic(&topic).unwrap();
+        assert!(repo.all_items().unwrap().is_empty());
+        let (before, removed) = restore(&repo, &backups, &first.path).unwrap();
+        assert!(removed.is_empty());
+        assert_eq!(repo.all_items().unwrap().len(), 1);
+
+        assert_eq!(list(&repo, &backups).unwrap(), vec![first.clone(), before]);
+        assert_eq!(prune(&repo, &backups, 1).unwrap(), vec![first]);
+        assert!(restore(&repo, &backups, &backups.join("notes.txt")).is_err());
+        assert_eq!(repo.all_items().unwrap().len(), 1);
+
+        drop(repo);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn restores_databases_from_before_history() {
+        let dir = std::env::temp_dir().join(format!("finish-it-restore-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let repo = Repository::open(&dir.join("fit.db")).unwrap();
+        repo.add_item(&Item {
+            name: String::from("Read SICP"),
+            topicname: String::from("Books"),
+            ..Item::default()
+        })
+        .unwrap();
+
+        // The first release had no History.
+        let old = dir.join("old.db");
+        rusqlite::Connection::open(&old)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE topics(
+                    name VARCHAR(256) NOT NULL,
+                    created TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+                    PRIMARY KEY(name)
+                );
+                CREATE TABLE items(
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name VARCHAR(256) NOT NULL,
+                    topicname VARCHAR(256) NOT NULL,
+                    isrecurring INTEGER DEFAULT 0 NOT NULL,
+                    percentage INTEGER DEFAULT 0 NOT NULL,
+                    timesfinished INTEGER DEFAULT 0 NOT NULL,
+                    daylimit INTEGER DEFAULT 0 NOT NULL,
+                    created TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+                    FOREIGN KEY(topicname) REFERENCES topics(name)
+                );
+                INSERT INTO topics (name) VALUES ('Garden');
+                INSERT INTO items (name, topicname, percentage) VALUES ('Plant tomatoes', 'Garden', 30);",
+            )
+            .unwrap();
+        // Has the tables restoring checks for, but not the columns upgrading needs.
+        let broken = dir.join("broken.db");
+        rusqlite::Connection::open(&broken)
+            .unwrap()
+            .execute_batch(
+                "CREATE TABLE topics(name VARCHAR(256) PRIMARY KEY);
+                CREATE TABLE items(id INTEGER PRIMARY KEY, topicname VARCHAR(256));",
+            )
+            .unwrap();
+
+        assert!(restore(&repo, &dir, &broken).is_err());
+        assert_eq!(repo.all_items().unwrap()[0].name, "Read SICP");
+        assert!(!dir.join("fit.db.restore").exists());
+
+        restore(&repo, &dir, &old).unwrap();
+        let items = repo.all_items().unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(
+            (items[0].name.as_str(), items[0].percentage),
+            ("Plant tomatoes", 30)
+        );
+        let mut item = items[0].clone();
+        item.increment_one();
+        repo.update_item(&item).unwrap();
+        assert_eq!(repo.topic_history("Garden").unwrap().len(), 1);
+
+        drop(repo);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
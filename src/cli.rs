@@ -0,0 +1,433 @@
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+use chrono::Utc;
+use finish_it::backup::{self, Snapshot};
+use finish_it::doctor;
+use finish_it::formats::{
+    collect_topics,
+    html::to_html,
+    ics::to_ics,
+    markdown::to_markdown,
+    org::{from_org, to_org},
+    taskwarrior::{from_taskwarrior, to_taskwarrior},
+    todotxt::{from_todotxt, to_todotxt},
+};
+use finish_it::report::{build_report, parse_period, ReportFormat};
+use finish_it::{Forecast, Item, Repository, Store};
+use serde::Serialize;
+
+use crate::config::Config;
+
+const USAGE: &str = "Usage: finish-it [--profile <NAME>] [--ephemeral] [COMMAND]
+
+Without a command the TUI is started.
+
+Options:
+  --profile <NAME>                  Use the database of profile NAME, created if needed. Profiles
+                                    keep separate Topics, Items and backups
+  --ephemeral                       Keep everything in memory for this session, the database is
+                                    neither read nor written
+
+Commands:
+  list [--topic <NAME>] [--json]    Print Items with their day limit and projected finish date
+  report [--since <PERIOD>] [--format text|markdown|json]
+                                    Review what was finished, progressed, added, deleted or missed
+                                    in the last PERIOD (e.g. 7d, 2w, 1m; defaults to 7d)
+  export (--markdown | --html | --ics | --todotxt | --taskwarrior | --org) [--topic <NAME>] [--output <FILE>]
+                                    Export Topics and their Items, to stdout unless FILE is given
+  import (--todotxt | --taskwarrior | --org) <FILE>
+                                    Import Items, updating the ones exported from here before
+  doctor [--fix]                    Check the database and its Items for states progress updates
+                                    never lead to, and repair them with --fix
+  backup [--list]                   Take a snapshot of the database now, or only list the snapshots
+                                    with --list
+  restore <FILE>                    Replace the database with a snapshot, by path or by its name in
+                                    the listing. The current database is snapshotted first
+  help                              Print this message";
+
+#[derive(Serialize)]
+struct ItemOutput<'a> {
+    #[serde(flatten)]
+    item: &'a Item,
+    days_left: String,
+    forecast: Forecast,
+}
+
+/// Runs the command given in `args` instead of starting the TUI.
+///
+/// `repo` is the database `store` reads from, or `None` in an ephemeral session. Output cut
+/// short by a closed pipe, like `finish-it list | head`, is not an error.
+pub fn run(
+    store: &dyn Store,
+    repo: Option<&Repository>,
+    config: &Config,
+    args: &[String],
+) -> Result<(), Box<dyn std::error::Error>> {
+    run_to(store, repo, config, args, &mut io::stdout().lock())
+}
+
+/// Runs the command like `run`, writing everything it prints to `out`.
+fn run_to(
+    store: &dyn Store,
+    repo: Option<&Repository>,
+    config: &Config,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match run_command(store, repo, config, args, out) {
+        Err(err)
+            if err
+                .downcast_ref::<io::Error>()
+                .is_some_and(|err| err.kind() == io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        result => result,
+    }
+}
+
+fn run_command(
+    store: &dyn Store,
+    repo: Option<&Repository>,
+    config: &Config,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match args[0].as_str() {
+        "list" => list(store, &args[1..], out),
+        "report" => report(store, &args[1..], out),
+        "export" => export(store, config, &args[1..], out),
+        "import" => import(store, &args[1..], out),
+        "doctor" => doctor(database(repo, "doctor")?, &args[1..], out),
+        "backup" => backup(database(repo, "backup")?, config, &args[1..], out),
+        "restore" => restore(database(repo, "restore")?, config, &args[1..], out),
+        "help" | "-h" | "--help" => {
+            writeln!(out, "{USAGE}")?;
+            Ok(())
+        }
+        other => Err(format!("unknown command '{other}'\n\n{USAGE}").into()),
+    }
+}
+
+/// The database for commands that work on the file itself rather than on its Items.
+fn database<'a>(repo: Option<&'a Repository>, command: &str) -> Result<&'a Repository, String> {
+    repo.ok_or_else(|| format!("{command} works on the database and can not run with --ephemeral"))
+}
+
+/// Returns the value following `flag` in `args`, if the flag was given.
+pub fn flag_value<'a>(args: &'a [String], flag: &str) -> Result<Option<&'a str>, String> {
+    match args.iter().position(|arg| arg == flag) {
+        Some(i) => match args.get(i + 1) {
+            Some(value) if !value.starts_with("--") => Ok(Some(value)),
+            _ => Err(format!("{flag} needs a value")),
+        },
+        None => Ok(None),
+    }
+}
+
+pub fn has_flag(args: &[String], flag: &str) -> bool {
+    args.iter().any(|arg| arg == flag)
+}
+
+/// Writes `content` to the file given with `--output`, or to `out`.
+fn write_output(
+    args: &[String],
+    content: &str,
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match flag_value(args, "--output")? {
+        Some(path) => std::fs::write(path, content)?,
+        None => out.write_all(content.as_bytes())?,
+    }
+    Ok(())
+}
+
+fn list(
+    store: &dyn Store,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut items = Vec::new();
+    let mut forecasts = Vec::new();
+    for (topic, topic_items) in collect_topics(store, flag_value(args, "--topic")?)? {
+        let history = store.topic_history(&topic.name)?;
+        for item in topic_items {
+            forecasts.push(item.forecast(&history));
+            items.push(item);
+        }
+    }
+
+    if has_flag(args, "--json") {
+        let output: Vec<ItemOutput> = items
+            .iter()
+            .zip(forecasts)
+            .map(|(item, forecast)| ItemOutput {
+                item,
+                days_left: item.days_left(),
+                forecast,
+            })
+            .collect();
+        writeln!(out, "{}", serde_json::to_string_pretty(&output)?)?;
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "{:>4}  {:<20} {:<24} {:>4}  {:>9}  {:<18}",
+        "ID", "Topic", "Name", "%", "Day Limit", "ETA"
+    )?;
+    for (item, forecast) in items.iter().zip(forecasts) {
+        writeln!(
+            out,
+            "{:>4}  {:<20} {:<24} {:>4}  {:>9}  {:<18}",
+            item.id,
+            item.topicname,
+            item.name,
+            item.percentage,
+            item.days_left(),
+            item.eta_str(&forecast)
+        )?;
+    }
+
+    Ok(())
+}
+
+fn report(
+    store: &dyn Store,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let period = parse_period(flag_value(args, "--since")?.unwrap_or("7d"))?;
+    let format: ReportFormat = flag_value(args, "--format")?.unwrap_or("text").parse()?;
+
+    let report = build_report(store, Utc::now() - period)?;
+    writeln!(out, "{}", report.render(format)?)?;
+    Ok(())
+}
+
+fn export(
+    store: &dyn Store,
+    config: &Config,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let topics = collect_topics(store, flag_value(args, "--topic")?)?;
+
+    let content = if has_flag(args, "--markdown") {
+        to_markdown(&topics, config.bar_width)
+    } else if has_flag(args, "--html") {
+        to_html(&topics)
+    } else if has_flag(args, "--ics") {
+        to_ics(&topics)
+    } else if has_flag(args, "--todotxt") {
+        to_todotxt(&topics)
+    } else if has_flag(args, "--taskwarrior") {
+        to_taskwarrior(&topics)?
+    } else if has_flag(args, "--org") {
+        to_org(&topics)
+    } else {
+        return Err(
+            "export needs a format: --markdown, --html, --ics, --todotxt, --taskwarrior or --org"
+                .into(),
+        );
+    };
+    write_output(args, &content, out)
+}
+
+fn import(
+    store: &dyn Store,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let items = if let Some(path) = flag_value(args, "--todotxt")? {
+        from_todotxt(&std::fs::read_to_string(path)?)?
+    } else if let Some(path) = flag_value(args, "--taskwarrior")? {
+        from_taskwarrior(&std::fs::read_to_string(path)?)?
+    } else if let Some(path) = flag_value(args, "--org")? {
+        from_org(&std::fs::read_to_string(path)?)?
+    } else {
+        return Err(
+            "import needs a format and a file: --todotxt <FILE>, --taskwarrior <FILE> or --org <FILE>"
+                .into(),
+        );
+    };
+
+    let count = store.import_items(&items)?;
+    writeln!(out, "Imported {count} items")?;
+    Ok(())
+}
+
+fn doctor(
+    repo: &Repository,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let findings = match has_flag(args, "--fix") {
+        true => {
+            let fixed = doctor::fix(repo)?;
+            for finding in fixed.iter() {
+                writeln!(out, "fixed: {finding}")?;
+            }
+            doctor::check(repo)?
+        }
+        false => doctor::check(repo)?,
+    };
+
+    for finding in findings.iter() {
+        writeln!(out, "{finding}")?;
+    }
+    match (findings.len(), findings.iter().all(|f| f.is_fixable())) {
+        (0, _) => {
+            writeln!(out, "No problems found")?;
+            Ok(())
+        }
+        (n, true) => Err(format!("{n} problems found, run doctor --fix to repair them").into()),
+        (n, false) => Err(format!(
+            "{n} problems found, restore a copy of the database to repair the corruption"
+        )
+        .into()),
+    }
+}
+
+/// Prints the snapshots of `repo`, oldest first.
+fn print_snapshots(snapshots: &[Snapshot], out: &mut dyn Write) -> io::Result<()> {
+    if snapshots.is_empty() {
+        writeln!(out, "No snapshots yet")?;
+    }
+    for snapshot in snapshots {
+        let name = snapshot.path.file_name().unwrap_or_default();
+        writeln!(
+            out,
+            "{}  {}",
+            snapshot.taken.format("%Y-%m-%d %H:%M:%S"),
+            name.to_string_lossy()
+        )?;
+    }
+    Ok(())
+}
+
+fn backup(
+    repo: &Repository,
+    config: &Config,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = config.backup_dir();
+    if !has_flag(args, "--list") {
+        let snapshot = backup::take(repo, &dir)?;
+        writeln!(out, "Saved {}", snapshot.path.display())?;
+        if config.backup.keep > 0 {
+            backup::prune(repo, &dir, config.backup.keep)?;
+        }
+    }
+    writeln!(out, "Snapshots in {}:", dir.display())?;
+    print_snapshots(&backup::list(repo, &dir)?, out)?;
+    Ok(())
+}
+
+fn restore(
+    repo: &Repository,
+    config: &Config,
+    args: &[String],
+    out: &mut dyn Write,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = config.backup_dir();
+    let file = match args.first() {
+        Some(file) => PathBuf::from(file),
+        None => {
+            writeln!(out, "Snapshots in {}:", dir.display())?;
+            print_snapshots(&backup::list(repo, &dir)?, out)?;
+            return Err("restore needs the snapshot to restore".into());
+        }
+    };
+    // Names from the listing are looked up in the backups directory.
+    let path = match file.exists() || file.components().count() > 1 {
+        true => file,
+        false => dir.join(file),
+    };
+
+    let (before, removed) = backup::restore(repo, &dir, &path)?;
+    writeln!(out, "Restored {}", path.display())?;
+    for item in removed {
+        writeln!(
+            out,
+            "Removed Item {} \"{}\", its Topic \"{}\" had been deleted",
+            item.id, item.name, item.topicname
+        )?;
+    }
+    writeln!(
+        out,
+        "The database before the restore is in {}",
+        before.path.display()
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use finish_it::MemoryStore;
+
+    use super::*;
+
+    /// Stdout of `finish-it … | head` once `head` has exited.
+    struct ClosedPipe;
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn closed_pipes_end_every_command_quietly() {
+        let repo = Repository::open_in_memory().unwrap();
+        let config = Config::default();
+        let path = std::env::temp_dir().join(format!("finish-it-cli-{}.org", std::process::id()));
+        std::fs::write(&path, "* Books\n** TODO Read SICP [40%]\n").unwrap();
+        let import = args(&["import", "--org", path.to_str().unwrap()]);
+
+        for command in [
+            import,
+            args(&["list"]),
+            args(&["list", "--json"]),
+            args(&["report"]),
+            args(&["export", "--markdown"]),
+            args(&["doctor"]),
+            args(&["help"]),
+        ] {
+            let result = run_to(&repo, Some(&repo), &config, &command, &mut ClosedPipe);
+            assert!(result.is_ok(), "{command:?}: {result:?}");
+        }
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(repo.all_items().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn other_errors_still_fail() {
+        let store = MemoryStore::new();
+        let config = Config::default();
+        let mut out = Vec::new();
+        let doctor = run_to(&store, None, &config, &args(&["doctor"]), &mut out);
+        assert!(doctor.is_err());
+        let report = run_to(
+            &store,
+            None,
+            &config,
+            &args(&["report", "--since", "0d"]),
+            &mut out,
+        );
+        assert!(report.is_err());
+
+        run_to(&store, None, &config, &args(&["list"]), &mut out).unwrap();
+        assert!(String::from_utf8(out).unwrap().starts_with("  ID  Topic"));
+    }
+}
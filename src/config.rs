@@ -0,0 +1,465 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::keymap::{KeyMap, KeysConfig};
+use crate::theme::{Theme, ThemeConfig};
+use crate::MenuItem;
+
+const APP_DIR: &str = "finish-it";
+const CONFIG_FILE: &str = "config.toml";
+const LEGACY_DB_PATH: &str = "var/fit.db";
+/// The profile whose database is `db_path`.
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Path of the sqlite database. A leading `~/` is expanded to the home directory.
+    pub db_path: PathBuf,
+    /// Profile opened at startup, unless `--profile` picks another one.
+    pub profile: String,
+    /// Databases of profiles that are not kept in `profiles/` next to `db_path`.
+    pub profiles: HashMap<String, PathBuf>,
+    /// How often the screen is redrawn without input, in milliseconds.
+    pub tick_rate_ms: u64,
+    /// Number of characters in the progress bars.
+    pub bar_width: usize,
+    pub popup: PopupConfig,
+    /// Tabs shown in the Menu, in order. Quit is always shown last.
+    pub tabs: Vec<MenuItem>,
+    pub defaults: ItemDefaults,
+    pub backup: BackupConfig,
+    /// Capture the mouse to click and scroll. Turn off to select text with the mouse instead.
+    pub mouse: bool,
+    /// Keybinding preset and per-action overrides.
+    pub keys: KeysConfig,
+    /// Bindings built from `keys` once the file has been read.
+    #[serde(skip)]
+    pub keymap: KeyMap,
+    /// Name of a built-in theme or of one from `themes`.
+    pub theme: String,
+    pub themes: HashMap<String, ThemeConfig>,
+    /// Colors of `theme`, or none at all if `NO_COLOR` is set.
+    #[serde(skip)]
+    pub palette: Theme,
+}
+
+/// Size of the Update and Delete popups in percent of the terminal.
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct PopupConfig {
+    pub width: u16,
+    pub height: u16,
+}
+
+/// Values the Add tab starts with for new Items.
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ItemDefaults {
+    pub recurring: bool,
+    pub day_limit: usize,
+}
+
+/// Where snapshots of the database go and how many are kept.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackupConfig {
+    /// Snapshots kept by the daily backup at startup. 0 turns the daily backup off.
+    pub keep: usize,
+    /// Defaults to `backups/` next to the database.
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            db_path: default_db_path(),
+            profile: String::from(DEFAULT_PROFILE),
+            profiles: HashMap::new(),
+            tick_rate_ms: 200,
+            bar_width: 20,
+            popup: PopupConfig::default(),
+            tabs: vec![
+                MenuItem::Home,
+                MenuItem::Instances,
+                MenuItem::Add,
+                MenuItem::Activity,
+            ],
+            defaults: ItemDefaults::default(),
+            backup: BackupConfig::default(),
+            mouse: true,
+            keys: KeysConfig::default(),
+            keymap: KeyMap::default(),
+            theme: String::from("dark"),
+            themes: HashMap::new(),
+            palette: Theme::default(),
+        }
+    }
+}
+
+impl Default for PopupConfig {
+    fn default() -> Self {
+        Self {
+            width: 60,
+            height: 20,
+        }
+    }
+}
+
+impl Default for BackupConfig {
+    fn default() -> Self {
+        Self { keep: 7, dir: None }
+    }
+}
+
+/// Keeps using `var/fit.db` for databases created before the config file existed.
+fn default_db_path() -> PathBuf {
+    let legacy = PathBuf::from(LEGACY_DB_PATH);
+    if legacy.exists() {
+        return legacy;
+    }
+    dirs::data_dir()
+        .map(|dir| dir.join(APP_DIR).join("fit.db"))
+        .unwrap_or(legacy)
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+    Invalid {
+        path: PathBuf,
+        key: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, err) => {
+                write!(f, "could not read config file {}: {}", path.display(), err)
+            }
+            ConfigError::Parse(path, err) => {
+                write!(f, "invalid config file {}: {}", path.display(), err)
+            }
+            ConfigError::Invalid { path, key, message } => {
+                write!(
+                    f,
+                    "invalid config file {}: `{}` {}",
+                    path.display(),
+                    key,
+                    message
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Location of the config file, `$XDG_CONFIG_HOME/finish-it/config.toml` on Linux.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(APP_DIR).join(CONFIG_FILE))
+    }
+
+    /// Loads the config file if there is one, falling back to the defaults otherwise.
+    ///
+    /// A non-empty `NO_COLOR` environment variable turns all colors off, whatever the theme.
+    pub fn load() -> Result<Self, ConfigError> {
+        let mut config = match Self::path() {
+            Some(path) if path.exists() => Self::load_from(&path)?,
+            _ => Self::default(),
+        };
+        if std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+            config.palette = Theme::no_color();
+        }
+        Ok(config)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self, ConfigError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|err| ConfigError::Read(path.into(), err))?;
+        let mut config: Config =
+            toml::from_str(&content).map_err(|err| ConfigError::Parse(path.into(), err))?;
+        config.validate(path)?;
+        config.db_path = expand_home(&config.db_path);
+        config.backup.dir = config.backup.dir.as_deref().map(expand_home);
+        for path in config.profiles.values_mut() {
+            *path = expand_home(path);
+        }
+        config.keymap =
+            KeyMap::from_config(&config.keys).map_err(|(key, message)| ConfigError::Invalid {
+                path: path.into(),
+                key,
+                message,
+            })?;
+        config.palette =
+            Theme::from_config(&config.theme, &config.themes).map_err(|(key, message)| {
+                ConfigError::Invalid {
+                    path: path.into(),
+                    key,
+                    message,
+                }
+            })?;
+        Ok(config)
+    }
+
+    /// Directory the snapshots of the database are kept in.
+    pub fn backup_dir(&self) -> PathBuf {
+        match &self.backup.dir {
+            Some(dir) => dir.clone(),
+            None => self
+                .database()
+                .parent()
+                .unwrap_or(Path::new("."))
+                .join("backups"),
+        }
+    }
+
+    /// Database of the active profile.
+    pub fn database(&self) -> PathBuf {
+        self.profile_path(&self.profile)
+    }
+
+    /// Database of the profile `name`. Profiles missing from `profiles` live in the `profiles`
+    /// directory next to `db_path`.
+    pub fn profile_path(&self, name: &str) -> PathBuf {
+        if name == DEFAULT_PROFILE {
+            return self.db_path.clone();
+        }
+        match self.profiles.get(name) {
+            Some(path) => path.clone(),
+            None => self.profiles_dir().join(format!("{name}.db")),
+        }
+    }
+
+    fn profiles_dir(&self) -> PathBuf {
+        self.db_path
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join("profiles")
+    }
+
+    /// Makes `name` the active profile. Profiles that do not exist yet are created when opened.
+    pub fn set_profile(&mut self, name: &str) -> Result<(), String> {
+        check_profile_name(name)?;
+        self.profile = name.to_owned();
+        Ok(())
+    }
+
+    /// The default profile, then every configured or existing profile by name.
+    pub fn profile_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.profiles.keys().cloned().collect();
+        if let Ok(entries) = std::fs::read_dir(self.profiles_dir()) {
+            names.extend(entries.filter_map(|entry| {
+                let path = entry.ok()?.path();
+                match path.extension()?.to_str()? {
+                    "db" => Some(path.file_stem()?.to_str()?.to_owned()),
+                    _ => None,
+                }
+            }));
+        }
+        names.push(self.profile.clone());
+        names.retain(|name| name != DEFAULT_PROFILE);
+        names.sort();
+        names.dedup();
+        names.insert(0, String::from(DEFAULT_PROFILE));
+        names
+    }
+
+    fn validate(&self, path: &Path) -> Result<(), ConfigError> {
+        let invalid = |key: &str, message: String| ConfigError::Invalid {
+            path: path.into(),
+            key: key.into(),
+            message,
+        };
+
+        if self.db_path.as_os_str().is_empty() {
+            return Err(invalid("db_path", String::from("must not be empty")));
+        }
+        if !(10..=10_000).contains(&self.tick_rate_ms) {
+            return Err(invalid(
+                "tick_rate_ms",
+                format!("must be between 10 and 10000, got {}", self.tick_rate_ms),
+            ));
+        }
+        if !(1..=100).contains(&self.bar_width) {
+            return Err(invalid(
+                "bar_width",
+                format!("must be between 1 and 100, got {}", self.bar_width),
+            ));
+        }
+        if !(1..=100).contains(&self.popup.width) {
+            return Err(invalid(
+                "popup.width",
+                format!(
+                    "must be between 1 and 100 percent, got {}",
+                    self.popup.width
+                ),
+            ));
+        }
+        if !(1..=100).contains(&self.popup.height) {
+            return Err(invalid(
+                "popup.height",
+                format!(
+                    "must be between 1 and 100 percent, got {}",
+                    self.popup.height
+                ),
+            ));
+        }
+        if self
+            .backup
+            .dir
+            .as_ref()
+            .is_some_and(|dir| dir.as_os_str().is_empty())
+        {
+            return Err(invalid("backup.dir", String::from("must not be empty")));
+        }
+        check_profile_name(&self.profile).map_err(|message| invalid("profile", message))?;
+        for (name, path) in self.profiles.iter() {
+            let key = format!("profiles.{name}");
+            check_profile_name(name).map_err(|message| invalid(&key, message))?;
+            if name == DEFAULT_PROFILE {
+                return Err(invalid(&key, String::from("is set with `db_path`")));
+            }
+            if path.as_os_str().is_empty() {
+                return Err(invalid(&key, String::from("must not be empty")));
+            }
+        }
+        if self.tabs.is_empty() {
+            return Err(invalid("tabs", String::from("must list at least one tab")));
+        }
+        let mut seen = HashSet::new();
+        if let Some(tab) = self.tabs.iter().find(|tab| !seen.insert(**tab)) {
+            return Err(invalid(
+                "tabs",
+                format!("lists {} more than once", tab.title()),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Profile names end up in file names, so they can not point elsewhere.
+fn check_profile_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err(String::from("must not be empty"));
+    }
+    if name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(format!(
+            "'{name}' can not start with a dot or contain slashes"
+        ));
+    }
+    Ok(())
+}
+
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Loads `content` as a config file and returns the key `load_from` complains about.
+    fn invalid_key(content: &str) -> Option<String> {
+        // Tests run in parallel, each file needs its own name.
+        static FILES: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "finish-it-config-{}-{}.toml",
+            std::process::id(),
+            FILES.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::write(&path, content).unwrap();
+        let result = Config::load_from(&path);
+        std::fs::remove_file(&path).unwrap();
+        match result {
+            Ok(_) => None,
+            Err(ConfigError::Invalid { key, .. }) => Some(key),
+            Err(err) => Some(err.to_string()),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_files() {
+        assert_eq!(invalid_key(""), None);
+        assert_eq!(
+            invalid_key(
+                "db_path = \"~/fit.db\"\n\
+                 profile = \"work\"\n\
+                 tick_rate_ms = 10\n\
+                 bar_width = 100\n\
+                 tabs = [\"topics\", \"home\"]\n\
+                 [popup]\nwidth = 100\nheight = 1\n\
+                 [profiles]\nwork = \"/tmp/work.db\"\n"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn names_the_key_at_fault() {
+        for (content, key) in [
+            ("db_path = \"\"", "db_path"),
+            ("tick_rate_ms = 9", "tick_rate_ms"),
+            ("tick_rate_ms = 10001", "tick_rate_ms"),
+            ("bar_width = 0", "bar_width"),
+            ("[popup]\nwidth = 101", "popup.width"),
+            ("[popup]\nheight = 0", "popup.height"),
+            ("[backup]\ndir = \"\"", "backup.dir"),
+            ("profile = \"\"", "profile"),
+            ("profile = \"../other\"", "profile"),
+            ("profile = \".hidden\"", "profile"),
+            ("[profiles]\ndefault = \"x.db\"", "profiles.default"),
+            ("[profiles]\nwork = \"\"", "profiles.work"),
+            ("[profiles]\n\"a/b\" = \"x.db\"", "profiles.a/b"),
+            ("tabs = []", "tabs"),
+            ("tabs = [\"home\", \"add\", \"home\"]", "tabs"),
+            ("[keys.bindings]\njump = [\"j\"]", "keys.bindings.jump"),
+            ("theme = \"nope\"", "theme"),
+        ] {
+            assert_eq!(invalid_key(content).as_deref(), Some(key), "{content}");
+        }
+    }
+
+    #[test]
+    fn refuses_unknown_keys_and_wrong_types() {
+        for content in [
+            "colour = \"red\"",
+            "bar_width = \"wide\"",
+            "tabs = [\"quit\"]",
+        ] {
+            let message = invalid_key(content).unwrap();
+            assert!(message.starts_with("invalid config file"), "{message}");
+        }
+    }
+
+    #[test]
+    fn profiles_live_next_to_the_default_database() {
+        let mut config = Config {
+            db_path: PathBuf::from("/data/fit.db"),
+            profiles: HashMap::from([(String::from("work"), PathBuf::from("/work/fit.db"))]),
+            ..Config::default()
+        };
+        assert_eq!(config.database(), PathBuf::from("/data/fit.db"));
+        assert_eq!(config.profile_path("work"), PathBuf::from("/work/fit.db"));
+        assert_eq!(
+            config.profile_path("home"),
+            PathBuf::from("/data/profiles/home.db")
+        );
+        assert!(config.set_profile("../../etc/passwd").is_err());
+        config.set_profile("home").unwrap();
+        assert_eq!(config.database(), PathBuf::from("/data/profiles/home.db"));
+        assert_eq!(config.backup_dir(), PathBuf::from("/data/profiles/backups"));
+    }
+}
@@ -1,10 +1,37 @@
-use rusqlite::{Connection, Result};
+use std::path::Path;
 
-use super::add::TextAreaContainer;
-use crate::{Item, Topic};
-pub fn get_db_connection() -> Result<Connection, rusqlite::Error> {
-    let conn = Connection::open("var/fit.db")?;
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, OptionalExtension, Result};
 
+use crate::model::{History, Item, Topic};
+
+/// Schema changes `migrate` knows about. Version 1 added the CHECK constraints.
+const SCHEMA_VERSION: i64 = 1;
+
+pub fn get_db_connection(path: &Path) -> Result<Connection, rusqlite::Error> {
+    let conn = Connection::open(path)?;
+    // SQLite ignores the foreign keys, and with them ON DELETE CASCADE, unless asked not to.
+    conn.pragma_update(None, "foreign_keys", true)?;
+
+    let fresh = !table_exists(&conn, "items")?;
+    create_tables(&conn)?;
+    if fresh {
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    }
+
+    // Room for every statement below, so none of them has to be prepared twice.
+    conn.set_prepared_statement_cache_capacity(32);
+
+    Ok(conn)
+}
+
+fn table_exists(conn: &Connection, name: &str) -> Result<bool, rusqlite::Error> {
+    conn.prepare_cached("SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?1")?
+        .query_row((name,), |row| row.get::<_, i64>(0))
+        .map(|count| count > 0)
+}
+
+fn create_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
     conn.execute(
         "CREATE TABLE IF NOT EXISTS topics(
             name VARCHAR(256) NOT NULL,
@@ -19,10 +46,10 @@ pub fn get_db_connection() -> Result<Connection, rusqlite::Error> {
             id INTEGER PRIMARY KEY AUTOINCREMENT,
             name VARCHAR(256) NOT NULL,
             topicname VARCHAR(256) NOT NULL,
-            isrecurring INTEGER DEFAULT 0 NOT NULL,
-            percentage INTEGER DEFAULT 0 NOT NULL,
-            timesfinished INTEGER DEFAULT 0 NOT NULL,
-            daylimit INTEGER DEFAULT 0 NOT NULL,
+            isrecurring INTEGER DEFAULT 0 NOT NULL CHECK(isrecurring IN (0, 1)),
+            percentage INTEGER DEFAULT 0 NOT NULL CHECK(percentage BETWEEN 0 AND 100),
+            timesfinished INTEGER DEFAULT 0 NOT NULL CHECK(timesfinished >= 0),
+            daylimit INTEGER DEFAULT 0 NOT NULL CHECK(daylimit >= 0),
             created TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
             FOREIGN KEY(topicname) REFERENCES topics(name)
                 ON DELETE CASCADE
@@ -31,11 +58,119 @@ pub fn get_db_connection() -> Result<Connection, rusqlite::Error> {
         [],
     )?;
 
-    Ok(conn)
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS history(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            itemid INTEGER NOT NULL,
+            itemname VARCHAR(256) NOT NULL,
+            topicname VARCHAR(256) NOT NULL,
+            action VARCHAR(16) NOT NULL,
+            percentage INTEGER DEFAULT 0 NOT NULL CHECK(percentage BETWEEN 0 AND 100),
+            timesfinished INTEGER DEFAULT 0 NOT NULL CHECK(timesfinished >= 0),
+            delta INTEGER DEFAULT 0 NOT NULL,
+            created TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+        )",
+        [],
+    )?;
+
+    Ok(())
+}
+
+/// Brings a database made by an older version up to `SCHEMA_VERSION`, once.
+///
+/// Returns the Items it had to remove because their Topic was gone. Those were left behind
+/// by deleting a Topic while SQLite ignored the foreign keys.
+pub fn migrate(conn: &Connection) -> Result<Vec<Item>, rusqlite::Error> {
+    let version: i64 = conn.pragma_query_value(None, "user_version", |row| row.get(0))?;
+    if version >= SCHEMA_VERSION {
+        return Ok(Vec::new());
+    }
+
+    let tx = conn.unchecked_transaction()?;
+    let orphans = remove_orphans(&tx)?;
+    add_checks(&tx)?;
+    tx.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+    tx.commit()?;
+    Ok(orphans)
+}
+
+/// Items whose Topic does not exist.
+pub fn find_orphans(conn: &Connection) -> Result<Vec<Item>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, name, topicname, isrecurring, percentage, timesfinished, daylimit, created \
+        FROM items WHERE topicname NOT IN (SELECT name FROM topics) ORDER BY id",
+    )?;
+    let orphans = stmt.query_map([], item_from_row)?.collect();
+    orphans
+}
+
+/// Deletes Items whose Topic does not exist, logging the ones not logged as deleted yet.
+pub fn remove_orphans(conn: &Connection) -> Result<Vec<Item>, rusqlite::Error> {
+    let orphans = find_orphans(conn)?;
+
+    conn.execute(
+        "INSERT INTO history (itemid, itemname, topicname, action, percentage, timesfinished) \
+        SELECT id, name, topicname, 'delete', percentage, timesfinished \
+        FROM items \
+        WHERE topicname NOT IN (SELECT name FROM topics) \
+            AND id NOT IN (SELECT itemid FROM history WHERE action = 'delete')",
+        [],
+    )?;
+    conn.execute(
+        "DELETE FROM items WHERE topicname NOT IN (SELECT name FROM topics)",
+        [],
+    )?;
+    Ok(orphans)
+}
+
+/// Recreates items and history with their CHECK constraints, which SQLite can only add to
+/// new tables. Values out of range are clamped into it, ids and the id counters are kept.
+fn add_checks(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT name, seq FROM sqlite_sequence")?;
+    let sequences = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+        })?
+        .collect::<Result<Vec<(String, i64)>, rusqlite::Error>>()?;
+
+    conn.execute_batch(
+        "ALTER TABLE items RENAME TO items_old;
+        ALTER TABLE history RENAME TO history_old;",
+    )?;
+    create_tables(conn)?;
+    conn.execute_batch(
+        "INSERT INTO items \
+            SELECT id, name, topicname, \
+                CASE isrecurring WHEN 0 THEN 0 ELSE 1 END, \
+                MIN(MAX(percentage, 0), 100), MAX(timesfinished, 0), MAX(daylimit, 0), created \
+            FROM items_old;
+        INSERT INTO history \
+            SELECT id, itemid, itemname, topicname, action, \
+                MIN(MAX(percentage, 0), 100), MAX(timesfinished, 0), delta, created \
+            FROM history_old;
+        DROP TABLE items_old;
+        DROP TABLE history_old;
+        DELETE FROM sqlite_sequence;",
+    )?;
+
+    for (name, seq) in sequences {
+        conn.execute(
+            "INSERT INTO sqlite_sequence (name, seq) VALUES (?1, ?2)",
+            (name, seq),
+        )?;
+    }
+    Ok(())
+}
+
+/// Changes whenever another connection commits a change to the database.
+pub fn data_version(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    conn.pragma_query_value(None, "data_version", |row| row.get(0))
 }
 
 pub fn read_topics_from_db(conn: &Connection) -> Result<Vec<Topic>, rusqlite::Error> {
-    let mut stmt = conn.prepare("SELECT * FROM topics")?;
+    // Topic names are the key, the rowid keeps the order they were added in.
+    let mut stmt =
+        conn.prepare_cached("SELECT name, created FROM topics ORDER BY created, rowid")?;
     let event_iter = stmt.query_map([], |row| {
         Ok(Topic {
             name: row.get(0)?,
@@ -51,39 +186,30 @@ pub fn read_topics_from_db(conn: &Connection) -> Result<Vec<Topic>, rusqlite::Er
     Ok(topics)
 }
 
-pub fn read_items_count_from_db(
+pub fn read_items_from_db(
     conn: &Connection,
-    selected_event: &str,
-) -> Result<usize, rusqlite::Error> {
-    let mut stmt = conn.prepare(
-        format!("SELECT COUNT(*) FROM items WHERE topicname = \"{selected_event}\"").as_str(),
+    event_name: &str,
+) -> Result<Vec<Item>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, name, topicname, isrecurring, percentage, timesfinished, daylimit, created \
+        FROM items WHERE topicname = ?1 ORDER BY id",
     )?;
-    let mut rows = (stmt.query([]))?;
+    let item_iter = stmt.query_map((event_name,), item_from_row)?;
 
-    let row = rows.next().unwrap().expect("Invalid Event");
-    let item_count: usize = row.get(0).expect("Invalid Event");
+    let mut items = Vec::new();
+    for item in item_iter {
+        items.push(item?);
+    }
 
-    Ok(item_count)
+    Ok(items)
 }
 
-pub fn read_items_from_db(
-    conn: &Connection,
-    event_name: &str,
-) -> Result<Vec<Item>, rusqlite::Error> {
-    let mut stmt =
-        conn.prepare(format!("SELECT * FROM items WHERE topicname = \"{event_name}\"").as_str())?;
-    let item_iter = stmt.query_map([], |row| {
-        Ok(Item {
-            id: row.get(0)?,
-            name: row.get(1)?,
-            topicname: row.get(2)?,
-            isrecurring: row.get(3)?,
-            percentage: row.get(4)?,
-            timesfinished: row.get(5)?,
-            daylimit: row.get(6)?,
-            created: row.get(7)?,
-        })
-    })?;
+pub fn read_all_items_from_db(conn: &Connection) -> Result<Vec<Item>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, name, topicname, isrecurring, percentage, timesfinished, daylimit, created \
+        FROM items ORDER BY topicname, id",
+    )?;
+    let item_iter = stmt.query_map([], item_from_row)?;
 
     let mut items = Vec::new();
     for item in item_iter {
@@ -93,62 +219,525 @@ pub fn read_items_from_db(
     Ok(items)
 }
 
-pub fn insert_into_db(
-    conn: &Connection,
-    text_areas: &mut [TextAreaContainer],
-) -> Result<(), rusqlite::Error> {
-    let texts: Vec<String> = text_areas.iter().map(|ta| ta.get_inner_data()).collect();
-    let texts: Vec<&str> = texts.iter().map(std::ops::Deref::deref).collect();
+fn item_from_row(row: &rusqlite::Row) -> Result<Item, rusqlite::Error> {
+    Ok(Item {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        topicname: row.get(2)?,
+        isrecurring: row.get(3)?,
+        percentage: row.get(4)?,
+        timesfinished: row.get(5)?,
+        daylimit: row.get(6)?,
+        created: row.get(7)?,
+    })
+}
 
-    conn.execute(
-        "INSERT OR IGNORE INTO topics (name) VALUES (?1)",
-        (texts[0],),
-    )?;
+/// Stores a new Item with the progress it starts at, adding its Topic if needed.
+pub fn add_item(conn: &Connection, item: &Item) -> Result<(), rusqlite::Error> {
+    // A failed insert must not leave an empty Topic behind.
+    let tx = conn.unchecked_transaction()?;
+    tx.prepare_cached("INSERT OR IGNORE INTO topics (name) VALUES (?1)")?
+        .execute((&item.topicname,))?;
 
-    conn.execute(
-        "INSERT INTO items (name, topicname, isrecurring, percentage, timesfinished, daylimit) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-        (texts[1], texts[0], texts[2], texts[3], texts[4], texts[5]),
-    )?;
+    tx.prepare_cached(
+        "INSERT INTO items (name, topicname, isrecurring, percentage, timesfinished, daylimit) \
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+    )?
+    .execute((
+        &item.name,
+        &item.topicname,
+        item.isrecurring,
+        item.percentage,
+        item.timesfinished,
+        item.daylimit,
+    ))?;
+
+    tx.prepare_cached(
+        "INSERT INTO history (itemid, itemname, topicname, action, percentage, timesfinished) \
+        VALUES (?1, ?2, ?3, 'insert', ?4, ?5)",
+    )?
+    .execute((
+        tx.last_insert_rowid(),
+        &item.name,
+        &item.topicname,
+        item.percentage,
+        item.timesfinished,
+    ))?;
+    tx.commit()?;
+
+    Ok(())
+}
 
-    text_areas.iter_mut().for_each(|ta| {
-        ta.clear_text();
-        ta.inactivate();
-        ta.validate();
-    });
+/// Inserts an Item with all of its fields, or overwrites the Item with the same id.
+///
+/// Items with id 0 are always inserted as new Items.
+pub fn import_item(conn: &Connection, item: &Item) -> Result<(), rusqlite::Error> {
+    conn.prepare_cached("INSERT OR IGNORE INTO topics (name) VALUES (?1)")?
+        .execute((&item.topicname,))?;
 
-    text_areas[0].activate();
+    // Ids in the file only point at our Items if they were exported from here. Anything else
+    // with the same id is an unrelated Item that must not be overwritten.
+    let stored = conn
+        .prepare_cached(
+            "SELECT id, name, topicname, isrecurring, percentage, timesfinished, daylimit, created \
+            FROM items WHERE id = ?1",
+        )?
+        .query_row((item.id,), item_from_row)
+        .optional()?;
+    let id = match (item.id, stored) {
+        (0, _) => None,
+        (_, Some(stored)) if !stored.is_same_item(item) => None,
+        (id, _) => Some(id),
+    };
+    conn.prepare_cached(
+        "INSERT INTO items \
+            (id, name, topicname, isrecurring, percentage, timesfinished, daylimit, created) \
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8) \
+        ON CONFLICT(id) DO UPDATE SET \
+            name = excluded.name, \
+            topicname = excluded.topicname, \
+            isrecurring = excluded.isrecurring, \
+            percentage = excluded.percentage, \
+            timesfinished = excluded.timesfinished, \
+            daylimit = excluded.daylimit, \
+            created = excluded.created",
+    )?
+    .execute((
+        id,
+        &item.name,
+        &item.topicname,
+        item.isrecurring,
+        item.percentage,
+        item.timesfinished,
+        item.daylimit,
+        item.created,
+    ))?;
 
+    conn.prepare_cached(
+        "INSERT INTO history (itemid, itemname, topicname, action, percentage, timesfinished) \
+        VALUES (?1, ?2, ?3, 'import', ?4, ?5)",
+    )?
+    .execute((
+        id.unwrap_or(conn.last_insert_rowid() as usize),
+        &item.name,
+        &item.topicname,
+        item.percentage,
+        item.timesfinished,
+    ))?;
     Ok(())
 }
 
 pub fn update_item(conn: &Connection, item: &Item) -> Result<(), rusqlite::Error> {
-    conn.execute(
+    // The delta is only right if nothing changes the Item between reading and writing it.
+    let tx = conn.unchecked_transaction()?;
+    let (old_percentage, old_timesfinished): (u8, usize) = tx
+        .prepare_cached("SELECT percentage, timesfinished FROM items WHERE id = ?1")?
+        .query_row((item.id,), |row| Ok((row.get(0)?, row.get(1)?)))?;
+    let old_item = Item {
+        percentage: old_percentage,
+        timesfinished: old_timesfinished,
+        ..item.clone()
+    };
+
+    tx.prepare_cached(
         "UPDATE items \
         SET percentage = ?2, \
             timesfinished = ?3 \
         WHERE \
             id = ?1;",
-        (item.id, item.percentage, item.timesfinished),
-    )?;
+    )?
+    .execute((item.id, item.percentage, item.timesfinished))?;
+
+    tx.prepare_cached(
+        "INSERT INTO history \
+            (itemid, itemname, topicname, action, percentage, timesfinished, delta) \
+        VALUES (?1, ?2, ?3, 'update', ?4, ?5, ?6)",
+    )?
+    .execute((
+        item.id,
+        &item.name,
+        &item.topicname,
+        item.percentage,
+        item.timesfinished,
+        item.progress_points() - old_item.progress_points(),
+    ))?;
+    tx.commit()?;
     Ok(())
 }
 
+pub fn read_topic_history_from_db(
+    conn: &Connection,
+    topic_name: &str,
+) -> Result<Vec<History>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, itemid, itemname, topicname, action, percentage, timesfinished, delta, created \
+        FROM history WHERE itemid IN (SELECT id FROM items WHERE topicname = ?1) \
+        ORDER BY created, id",
+    )?;
+    let history_iter = stmt.query_map((topic_name,), history_from_row)?;
+
+    let mut history = Vec::new();
+    for entry in history_iter {
+        history.push(entry?);
+    }
+
+    Ok(history)
+}
+
+fn history_from_row(row: &rusqlite::Row) -> Result<History, rusqlite::Error> {
+    Ok(History {
+        id: row.get(0)?,
+        itemid: row.get(1)?,
+        itemname: row.get(2)?,
+        topicname: row.get(3)?,
+        action: row.get(4)?,
+        percentage: row.get(5)?,
+        timesfinished: row.get(6)?,
+        delta: row.get(7)?,
+        created: row.get(8)?,
+    })
+}
+
+pub fn read_history_from_db(
+    conn: &Connection,
+    since: DateTime<Utc>,
+) -> Result<Vec<History>, rusqlite::Error> {
+    let mut stmt = conn.prepare_cached(
+        "SELECT id, itemid, itemname, topicname, action, percentage, timesfinished, delta, created \
+        FROM history WHERE created >= ?1 ORDER BY created, id",
+    )?;
+    let history_iter = stmt.query_map((since,), history_from_row)?;
+
+    let mut history = Vec::new();
+    for entry in history_iter {
+        history.push(entry?);
+    }
+
+    Ok(history)
+}
+
+/// Deletes `item`, logging it in the History in the same transaction.
 pub fn delete_item(conn: &Connection, item: &Item) -> Result<(), rusqlite::Error> {
-    conn.execute(
+    let tx = conn.unchecked_transaction()?;
+    tx.prepare_cached(
+        "INSERT INTO history (itemid, itemname, topicname, action, percentage, timesfinished) \
+        SELECT id, name, topicname, 'delete', percentage, timesfinished \
+        FROM items \
+        WHERE id = ?1",
+    )?
+    .execute((item.id,))?;
+    tx.prepare_cached(
         "DELETE \
         FROM items \
         WHERE id = ?1",
-        (item.id,),
-    )?;
-    Ok(())
+    )?
+    .execute((item.id,))?;
+    tx.commit()
 }
 
+/// Deletes `event` and its Items, logging the Items in the History in the same transaction.
 pub fn delete_topic(conn: &Connection, event: &Topic) -> Result<(), rusqlite::Error> {
-    conn.execute(
+    let tx = conn.unchecked_transaction()?;
+    tx.prepare_cached(
+        "INSERT INTO history (itemid, itemname, topicname, action, percentage, timesfinished) \
+        SELECT id, name, topicname, 'delete', percentage, timesfinished \
+        FROM items \
+        WHERE topicname = ?1",
+    )?
+    .execute((&event.name,))?;
+    tx.prepare_cached(
         "DELETE \
         FROM topics \
         WHERE name = ?1",
-        (&event.name,),
-    )?;
-    Ok(())
+    )?
+    .execute((&event.name,))?;
+    tx.commit()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Topic names that break or rewrite queries built by pasting them into the SQL.
+    const ADVERSARIAL_NAMES: [&str; 8] = [
+        "Say \"hi\"",
+        "O'Brien's books",
+        "\"; DROP TABLE items; --",
+        "x' OR '1'='1",
+        "-- not a comment",
+        "one; two",
+        "Ünïcødé 📚 名前",
+        "100% _done_ \\",
+    ];
+
+    fn add(conn: &Connection, topic: &str, name: &str) {
+        let item = Item {
+            name: name.to_string(),
+            topicname: topic.to_string(),
+            percentage: 10,
+            ..Item::default()
+        };
+        add_item(conn, &item).unwrap();
+    }
+
+    #[test]
+    fn topic_names_are_stored_verbatim() {
+        let conn = get_db_connection(Path::new(":memory:")).unwrap();
+        add(&conn, "Books", "Read SICP");
+        for name in ADVERSARIAL_NAMES {
+            add(&conn, name, name);
+        }
+
+        let topics: Vec<String> = read_topics_from_db(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|topic| topic.name)
+            .collect();
+        assert_eq!(topics[1..], ADVERSARIAL_NAMES);
+        for name in ADVERSARIAL_NAMES {
+            let items = read_items_from_db(&conn, name).unwrap();
+            assert_eq!(items.len(), 1, "{name}");
+            assert_eq!(items[0].name, name);
+            assert_eq!(read_topic_history_from_db(&conn, name).unwrap().len(), 1);
+        }
+        assert_eq!(read_all_items_from_db(&conn).unwrap().len(), 9);
+    }
+
+    #[test]
+    fn adversarial_names_only_change_their_own_rows() {
+        let conn = get_db_connection(Path::new(":memory:")).unwrap();
+        add(&conn, "Books", "Read SICP");
+        for name in ADVERSARIAL_NAMES {
+            add(&conn, name, name);
+        }
+
+        for name in ADVERSARIAL_NAMES {
+            let mut item = read_items_from_db(&conn, name).unwrap().remove(0);
+            item.increment_one();
+            update_item(&conn, &item).unwrap();
+            assert_eq!(read_items_from_db(&conn, name).unwrap()[0].percentage, 11);
+
+            let topic = Topic {
+                name: name.to_string(),
+                ..Topic::default()
+            };
+            delete_topic(&conn, &topic).unwrap();
+            assert!(read_items_from_db(&conn, name).unwrap().is_empty());
+        }
+
+        let books = read_items_from_db(&conn, "Books").unwrap();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].percentage, 10);
+        let topics = read_topics_from_db(&conn).unwrap();
+        assert_eq!(topics.len(), 1);
+    }
+
+    /// The schema before version 1, without CHECK constraints.
+    const SCHEMA_V0: &str = "
+        CREATE TABLE topics(
+            name VARCHAR(256) NOT NULL,
+            created TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+            PRIMARY KEY(name)
+        );
+        CREATE TABLE items(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            name VARCHAR(256) NOT NULL,
+            topicname VARCHAR(256) NOT NULL,
+            isrecurring INTEGER DEFAULT 0 NOT NULL,
+            percentage INTEGER DEFAULT 0 NOT NULL,
+            timesfinished INTEGER DEFAULT 0 NOT NULL,
+            daylimit INTEGER DEFAULT 0 NOT NULL,
+            created TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL,
+            FOREIGN KEY(topicname) REFERENCES topics(name)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+        );
+        CREATE TABLE history(
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            itemid INTEGER NOT NULL,
+            itemname VARCHAR(256) NOT NULL,
+            topicname VARCHAR(256) NOT NULL,
+            action VARCHAR(16) NOT NULL,
+            percentage INTEGER DEFAULT 0 NOT NULL,
+            timesfinished INTEGER DEFAULT 0 NOT NULL,
+            delta INTEGER DEFAULT 0 NOT NULL,
+            created TIMESTAMP DEFAULT CURRENT_TIMESTAMP NOT NULL
+        );";
+
+    #[test]
+    fn migrating_removes_orphans_and_adds_checks() {
+        let path = std::env::temp_dir().join(format!("finish-it-v0-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        {
+            let old = Connection::open(&path).unwrap();
+            old.execute_batch(SCHEMA_V0).unwrap();
+            // Topic "Gone" was deleted while foreign keys were off, leaving its Item behind.
+            old.execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                INSERT INTO topics (name) VALUES ('Books');
+                INSERT INTO items (name, topicname, percentage) VALUES ('Read SICP', 'Books', 150);
+                INSERT INTO items (name, topicname) VALUES ('Left behind', 'Gone');
+                INSERT INTO items (name, topicname) VALUES ('Deleted', 'Books');
+                DELETE FROM items WHERE name = 'Deleted';",
+            )
+            .unwrap();
+        }
+
+        let conn = get_db_connection(&path).unwrap();
+        let orphans = migrate(&conn).unwrap();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].name, "Left behind");
+        assert!(migrate(&conn).unwrap().is_empty());
+
+        let items = read_all_items_from_db(&conn).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].percentage, 100);
+        let history = read_history_from_db(&conn, DateTime::default()).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(
+            (history[0].itemid, history[0].action.as_str()),
+            (2, "delete")
+        );
+
+        // Ids of deleted Items are still not handed out again.
+        add(&conn, "Books", "Read TAOCP");
+        assert_eq!(read_items_from_db(&conn, "Books").unwrap()[1].id, 4);
+
+        let mut item = items[0].clone();
+        item.percentage = 101;
+        let err = update_item(&conn, &item).unwrap_err();
+        assert_eq!(
+            err.sqlite_error_code(),
+            Some(rusqlite::ErrorCode::ConstraintViolation)
+        );
+
+        delete_topic(&conn, &Topic::default()).unwrap();
+        let books = Topic {
+            name: String::from("Books"),
+            ..Topic::default()
+        };
+        delete_topic(&conn, &books).unwrap();
+        assert!(read_all_items_from_db(&conn).unwrap().is_empty());
+
+        drop(conn);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn imports_only_overwrite_the_same_item() {
+        let conn = get_db_connection(Path::new(":memory:")).unwrap();
+        add(&conn, "Books", "Read SICP");
+        let mut exported = read_items_from_db(&conn, "Books").unwrap().remove(0);
+
+        exported.percentage = 60;
+        import_item(&conn, &exported).unwrap();
+        let books = read_items_from_db(&conn, "Books").unwrap();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].percentage, 60);
+
+        // Renamed in the other tool, but created at the same time.
+        let renamed = Item {
+            name: String::from("Read SICP, 2nd edition"),
+            ..exported.clone()
+        };
+        import_item(&conn, &renamed).unwrap();
+        let books = read_items_from_db(&conn, "Books").unwrap();
+        assert_eq!(books.len(), 1);
+        assert_eq!(books[0].name, "Read SICP, 2nd edition");
+
+        // Same id, but a different Item exported from another database.
+        let other = Item {
+            name: String::from("Water plants"),
+            topicname: String::from("Garden"),
+            percentage: 20,
+            created: exported.created - chrono::Duration::days(3),
+            ..exported.clone()
+        };
+        import_item(&conn, &other).unwrap();
+        let books = read_items_from_db(&conn, "Books").unwrap();
+        assert_eq!(
+            (books[0].name.as_str(), books[0].percentage),
+            ("Read SICP, 2nd edition", 60)
+        );
+        let garden = read_items_from_db(&conn, "Garden").unwrap();
+        assert_eq!(garden.len(), 1);
+        assert_ne!(garden[0].id, exported.id);
+        assert_eq!(garden[0].percentage, 20);
+    }
+
+    #[test]
+    fn failed_deletes_log_nothing() {
+        let conn = get_db_connection(Path::new(":memory:")).unwrap();
+        add(&conn, "Books", "Read SICP");
+        let item = read_items_from_db(&conn, "Books").unwrap().remove(0);
+        let topic = read_topics_from_db(&conn).unwrap().remove(0);
+        conn.execute_batch(
+            "CREATE TRIGGER keep_items BEFORE DELETE ON items \
+            BEGIN SELECT RAISE(ABORT, 'kept'); END;",
+        )
+        .unwrap();
+
+        assert!(delete_item(&conn, &item).is_err());
+        assert!(delete_topic(&conn, &topic).is_err());
+        let history = read_topic_history_from_db(&conn, "Books").unwrap();
+        assert!(history.iter().all(|entry| entry.action != "delete"));
+        assert_eq!(read_items_from_db(&conn, "Books").unwrap().len(), 1);
+
+        conn.execute_batch("DROP TRIGGER keep_items").unwrap();
+        delete_item(&conn, &item).unwrap();
+        assert!(read_items_from_db(&conn, "Books").unwrap().is_empty());
+    }
+
+    #[test]
+    fn failed_updates_change_nothing() {
+        let conn = get_db_connection(Path::new(":memory:")).unwrap();
+        add(&conn, "Books", "Read SICP");
+        let mut item = read_items_from_db(&conn, "Books").unwrap().remove(0);
+        conn.execute_batch(
+            "CREATE TRIGGER no_history BEFORE INSERT ON history \
+            BEGIN SELECT RAISE(ABORT, 'no history'); END;",
+        )
+        .unwrap();
+
+        item.percentage = 80;
+        assert!(update_item(&conn, &item).is_err());
+        assert_eq!(
+            read_items_from_db(&conn, "Books").unwrap()[0].percentage,
+            10
+        );
+
+        conn.execute_batch("DROP TRIGGER no_history").unwrap();
+        update_item(&conn, &item).unwrap();
+        assert_eq!(
+            read_items_from_db(&conn, "Books").unwrap()[0].percentage,
+            80
+        );
+    }
+
+    #[test]
+    fn topics_and_items_come_back_in_order() {
+        let conn = get_db_connection(Path::new(":memory:")).unwrap();
+        for (id, topic) in [(9, "Films"), (3, "Books"), (5, "Films"), (1, "Games")] {
+            let item = Item {
+                id,
+                name: format!("Item {id}"),
+                topicname: topic.to_string(),
+                ..Item::default()
+            };
+            import_item(&conn, &item).unwrap();
+        }
+
+        let topics: Vec<String> = read_topics_from_db(&conn)
+            .unwrap()
+            .into_iter()
+            .map(|topic| topic.name)
+            .collect();
+        assert_eq!(topics, ["Films", "Books", "Games"]);
+        let films: Vec<usize> = read_items_from_db(&conn, "Films")
+            .unwrap()
+            .iter()
+            .map(|item| item.id)
+            .collect();
+        assert_eq!(films, [5, 9]);
+    }
 }
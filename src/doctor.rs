@@ -0,0 +1,251 @@
+//! Finds rows in states progress updates never lead to and repairs the ones it can. Such rows
+//! come from imports, older versions, edits outside Finish It or the Add form, which takes the
+//! starting progress as typed.
+
+use std::fmt;
+
+use crate::db;
+use crate::model::Item;
+use crate::repository::Repository;
+
+/// Something wrong with the database or one of its Items.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// A line reported by `PRAGMA integrity_check`. Only a backup can fix these.
+    Corrupt(String),
+    /// A one-off Item finished more than once.
+    FinishedMoreThanOnce,
+    /// A one-off Item at 100% that is not counted as finished.
+    DoneButNotFinished,
+    /// A one-off Item counted as finished below 100%.
+    FinishedButNotDone,
+    /// A recurring Item at 100% that did not start its next round.
+    NotWrapped,
+    /// An Item whose Topic does not exist.
+    Orphan,
+}
+
+/// A Problem found by `check`, with the Item it was found on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Finding {
+    /// What is wrong.
+    pub problem: Problem,
+    /// The Item at fault, or `None` for Problems with the database itself.
+    pub item: Option<Item>,
+}
+
+impl Finding {
+    /// Whether `fix` can repair it.
+    pub fn is_fixable(&self) -> bool {
+        !matches!(self.problem, Problem::Corrupt(_))
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(item) = &self.item {
+            write!(
+                f,
+                "Item {} \"{}\" in {}: ",
+                item.id, item.name, item.topicname
+            )?;
+        }
+        match &self.problem {
+            Problem::Corrupt(message) => write!(f, "database corrupt: {message}"),
+            Problem::FinishedMoreThanOnce => write!(
+                f,
+                "one-off Item finished {} times",
+                self.item.as_ref().map_or(0, |item| item.timesfinished)
+            ),
+            Problem::DoneButNotFinished => write!(f, "at 100% but not finished"),
+            Problem::FinishedButNotDone => write!(
+                f,
+                "finished at {}%",
+                self.item.as_ref().map_or(0, |item| item.percentage)
+            ),
+            Problem::NotWrapped => write!(f, "recurring Item at 100% did not start over"),
+            Problem::Orphan => write!(f, "its Topic does not exist"),
+        }
+    }
+}
+
+/// What is wrong with `item` according to the rules `Item::increment_one` and
+/// `Item::finish_once` follow, if anything.
+fn item_problem(item: &Item) -> Option<Problem> {
+    match (item.isrecurring, item.percentage, item.timesfinished) {
+        (0, _, n) if n > 1 => Some(Problem::FinishedMoreThanOnce),
+        (0, 100, 0) => Some(Problem::DoneButNotFinished),
+        (0, p, 1) if p < 100 => Some(Problem::FinishedButNotDone),
+        (1, p, _) if p >= 100 => Some(Problem::NotWrapped),
+        _ => None,
+    }
+}
+
+/// Runs SQLite's integrity check and checks every Item. Changes nothing.
+pub fn check(repo: &Repository) -> Result<Vec<Finding>, rusqlite::Error> {
+    let conn = repo.connection();
+    let mut findings = Vec::new();
+
+    let mut stmt = conn.prepare("PRAGMA integrity_check")?;
+    for line in stmt.query_map([], |row| row.get::<_, String>(0))? {
+        let line = line?;
+        if line != "ok" {
+            findings.push(Finding {
+                problem: Problem::Corrupt(line),
+                item: None,
+            });
+        }
+    }
+
+    for item in db::find_orphans(conn)? {
+        findings.push(Finding {
+            problem: Problem::Orphan,
+            item: Some(item),
+        });
+    }
+    for item in db::read_all_items_from_db(conn)? {
+        if let Some(problem) = item_problem(&item) {
+            findings.push(Finding {
+                problem,
+                item: Some(item),
+            });
+        }
+    }
+    Ok(findings)
+}
+
+/// Repairs everything `check` finds that can be repaired, in a single transaction, and
+/// returns what it repaired.
+///
+/// Progress is kept as it is: one-off Items count as finished exactly when they are at 100%,
+/// recurring Items at 100% start their next round at 0%. Orphans are deleted, as their Topic
+/// was. Every repair is logged in the History as a `repair`, deletions as a `delete`.
+pub fn fix(repo: &Repository) -> Result<Vec<Finding>, rusqlite::Error> {
+    let conn = repo.connection();
+    let tx = conn.unchecked_transaction()?;
+    let mut fixed: Vec<Finding> = check(repo)?
+        .into_iter()
+        .filter(|finding| finding.is_fixable() && finding.problem != Problem::Orphan)
+        .collect();
+
+    for finding in fixed.iter() {
+        let item = match &finding.item {
+            Some(item) => item,
+            None => continue,
+        };
+        let (percentage, timesfinished) = match finding.problem {
+            Problem::NotWrapped => (item.percentage - 100, item.timesfinished + 1),
+            _ => (item.percentage, (item.percentage == 100) as usize),
+        };
+        let repaired = Item {
+            percentage,
+            timesfinished,
+            ..item.clone()
+        };
+        tx.prepare_cached("UPDATE items SET percentage = ?2, timesfinished = ?3 WHERE id = ?1")?
+            .execute((item.id, percentage, timesfinished))?;
+        tx.prepare_cached(
+            "INSERT INTO history \
+                (itemid, itemname, topicname, action, percentage, timesfinished, delta) \
+            VALUES (?1, ?2, ?3, 'repair', ?4, ?5, ?6)",
+        )?
+        .execute((
+            item.id,
+            &item.name,
+            &item.topicname,
+            percentage,
+            timesfinished,
+            repaired.progress_points() - item.progress_points(),
+        ))?;
+    }
+    fixed.extend(db::remove_orphans(&tx)?.into_iter().map(|item| Finding {
+        problem: Problem::Orphan,
+        item: Some(item),
+    }));
+
+    tx.commit()?;
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::Store;
+
+    fn item(name: &str, isrecurring: u8, percentage: u8, timesfinished: usize) -> Item {
+        Item {
+            name: name.to_string(),
+            topicname: String::from("Books"),
+            isrecurring,
+            percentage,
+            timesfinished,
+            ..Item::default()
+        }
+    }
+
+    #[test]
+    fn finds_and_fixes_broken_items() {
+        let repo = Repository::open_in_memory().unwrap();
+        let items = [
+            item("Fine", 0, 40, 0),
+            item("Twice", 0, 100, 3),
+            item("Done", 0, 100, 0),
+            item("Undone", 0, 60, 1),
+            item("Stuck", 1, 100, 2),
+        ];
+        repo.import_items(&items).unwrap();
+        let conn = repo.connection();
+        conn.pragma_update(None, "foreign_keys", false).unwrap();
+        conn.execute(
+            "UPDATE items SET topicname = 'Gone' WHERE name = 'Fine'",
+            [],
+        )
+        .unwrap();
+        conn.pragma_update(None, "foreign_keys", true).unwrap();
+
+        let problems: Vec<(String, Problem)> = check(&repo)
+            .unwrap()
+            .into_iter()
+            .map(|finding| (finding.item.unwrap().name, finding.problem))
+            .collect();
+        assert_eq!(
+            problems,
+            vec![
+                (String::from("Fine"), Problem::Orphan),
+                (String::from("Twice"), Problem::FinishedMoreThanOnce),
+                (String::from("Done"), Problem::DoneButNotFinished),
+                (String::from("Undone"), Problem::FinishedButNotDone),
+                (String::from("Stuck"), Problem::NotWrapped),
+            ]
+        );
+
+        assert_eq!(fix(&repo).unwrap().len(), 5);
+        assert!(check(&repo).unwrap().is_empty());
+        let fixed: Vec<(u8, usize)> = repo
+            .all_items()
+            .unwrap()
+            .iter()
+            .map(|item| (item.percentage, item.timesfinished))
+            .collect();
+        assert_eq!(fixed, vec![(100, 1), (100, 1), (60, 0), (0, 3)]);
+
+        let history = repo
+            .history(chrono::DateTime::<chrono::Utc>::MIN_UTC)
+            .unwrap();
+        let logged: Vec<(&str, &str, i64)> = history
+            .iter()
+            .filter(|entry| entry.action != "import")
+            .map(|entry| (entry.itemname.as_str(), entry.action.as_str(), entry.delta))
+            .collect();
+        assert_eq!(
+            logged,
+            vec![
+                ("Twice", "repair", 0),
+                ("Done", "repair", 0),
+                ("Undone", "repair", 0),
+                ("Stuck", "repair", 0),
+                ("Fine", "delete", 0),
+            ]
+        );
+    }
+}
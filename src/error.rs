@@ -0,0 +1,45 @@
+//! What can go wrong reading or changing a Store.
+
+use std::fmt;
+
+use crate::repository::describe_error;
+
+/// Why a [`Store`](crate::Store) could not read or change its Topics and Items.
+#[derive(Debug)]
+pub enum StoreError {
+    /// The database failed or refused the change.
+    Sqlite(rusqlite::Error),
+    /// The Item breaks a rule every Store keeps, like a percentage above 100. Nothing was
+    /// changed.
+    Invalid(String),
+    /// No Item has the id of the one to change, someone else may have deleted it.
+    NotFound(usize),
+    /// No Topic has the name asked for.
+    NoSuchTopic(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StoreError::Sqlite(err) => write!(f, "{}", describe_error(err)),
+            StoreError::Invalid(reason) => write!(f, "The change was refused: {reason}"),
+            StoreError::NotFound(id) => write!(f, "Item {id} does not exist anymore."),
+            StoreError::NoSuchTopic(name) => write!(f, "There is no Topic called '{name}'."),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            StoreError::Sqlite(err) => Some(err),
+            StoreError::Invalid(_) | StoreError::NotFound(_) | StoreError::NoSuchTopic(_) => None,
+        }
+    }
+}
+
+impl From<rusqlite::Error> for StoreError {
+    fn from(err: rusqlite::Error) -> Self {
+        StoreError::Sqlite(err)
+    }
+}
@@ -0,0 +1,112 @@
+//! HTML export, a self contained page with a progress bar per Item.
+
+use std::fmt::Write;
+
+use super::item_details;
+use crate::model::{Item, Topic};
+
+const STYLE: &str =
+    "body { font-family: sans-serif; max-width: 50em; margin: 2em auto; color: #222; }
+h1 { color: #d33; }
+ul { list-style: none; padding: 0; }
+li { margin: 0.6em 0; }
+.done .name { text-decoration: line-through; color: #777; }
+.bar { background: #ddd; border-radius: 3px; height: 0.8em; width: 100%; }
+.fill { background: #3a3; border-radius: 3px; height: 100%; }
+.details { color: #555; font-size: 0.9em; }";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Renders Topics as a self contained HTML page with a progress bar per Item.
+pub fn to_html(topics: &[(Topic, Vec<Item>)]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Finish It!</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>Finish It!</h1>\n"
+    );
+    for (topic, items) in topics {
+        let _ = writeln!(out, "<h2>{}</h2>\n<ul>", escape(&topic.name));
+        for item in items {
+            let class = if item.is_done() {
+                " class=\"done\""
+            } else {
+                ""
+            };
+            let _ = writeln!(
+                out,
+                "<li{}><span class=\"name\">{}</span> <span class=\"details\">{}</span>\n<div class=\"bar\"><div class=\"fill\" style=\"width: {}%\"></div></div></li>",
+                class,
+                escape(&item.name),
+                escape(&item_details(item)),
+                item.percentage.min(100)
+            );
+        }
+        out.push_str("</ul>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, percentage: u8) -> Item {
+        Item {
+            name: name.to_string(),
+            topicname: String::from("R&D <lab>"),
+            percentage,
+            ..Item::default()
+        }
+    }
+
+    fn unescape(text: &str) -> String {
+        text.replace("&quot;", "\"")
+            .replace("&gt;", ">")
+            .replace("&lt;", "<")
+            .replace("&amp;", "&")
+    }
+
+    /// The Item names on the page, read back the way a browser shows them.
+    fn names(html: &str) -> Vec<String> {
+        html.split("<span class=\"name\">")
+            .skip(1)
+            .map(|rest| unescape(&rest[..rest.find("</span>").unwrap()]))
+            .collect()
+    }
+
+    #[test]
+    fn names_survive_a_round_trip() {
+        let items = vec![
+            item("Read SICP", 40),
+            item("<script>alert(\"hi\")</script>", 100),
+            item("Fish & Chips &amp; more", 0),
+            item("Ünïcødé 📚", 99),
+        ];
+        let topic = Topic {
+            name: String::from("R&D <lab>"),
+            ..Topic::default()
+        };
+        let html = to_html(&[(topic, items.clone())]);
+        assert!(html.contains("<h2>R&amp;D &lt;lab&gt;</h2>"));
+        assert!(!html.contains("<script>"));
+        let expected: Vec<String> = items.into_iter().map(|item| item.name).collect();
+        assert_eq!(names(&html), expected);
+    }
+
+    #[test]
+    fn done_items_are_marked_and_bars_are_capped() {
+        let html = to_html(&[(
+            Topic::default(),
+            vec![item("Done", 100), item("Half", 50), item("Odd", 250)],
+        )]);
+        assert!(html.contains("<li class=\"done\"><span class=\"name\">Done</span>"));
+        assert!(html.contains("<li><span class=\"name\">Half</span>"));
+        assert!(html.contains("style=\"width: 50%\""));
+        assert!(html.contains("style=\"width: 100%\"></div></div></li>\n</ul>"));
+        assert!(!html.contains("width: 250%"));
+    }
+}
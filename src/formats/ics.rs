@@ -0,0 +1,189 @@
+//! iCalendar export of the Items' deadlines.
+
+use chrono::{DateTime, Utc};
+
+use crate::model::{Item, Topic};
+
+/// Escapes a TEXT value. Line breaks become `\n`, whether they were written as CRLF or LF.
+fn escape(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line to at most 75 octets per line as RFC 5545 requires.
+fn fold(line: &str) -> String {
+    let mut out = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            out.push_str("\r\n ");
+            width = 1;
+        }
+        out.push(c);
+        width += c.len_utf8();
+    }
+    out.push_str("\r\n");
+    out
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn item_lines(item: &Item, now: DateTime<Utc>) -> Vec<String> {
+    let uid = format!("UID:finish-it-item-{}", item.id);
+    let summary = format!(
+        "SUMMARY:{}",
+        escape(&format!("{}: {}", item.topicname, item.name))
+    );
+    let description = format!(
+        "DESCRIPTION:{}",
+        escape(&format!(
+            "{}% completed, finished {} times",
+            item.percentage, item.timesfinished
+        ))
+    );
+
+    match (item.isrecurring, item.deadline()) {
+        // Recurring Items repeat every `daylimit` days starting from their creation.
+        (1, Some(_)) => vec![
+            String::from("BEGIN:VEVENT"),
+            uid,
+            format!("DTSTAMP:{}", timestamp(now)),
+            format!("DTSTART;VALUE=DATE:{}", item.created.format("%Y%m%d")),
+            format!("RRULE:FREQ=DAILY;INTERVAL={}", item.daylimit),
+            summary,
+            description,
+            String::from("END:VEVENT"),
+        ],
+        (_, Some(deadline)) => {
+            let status = if item.is_done() {
+                "COMPLETED"
+            } else if item.percentage > 0 {
+                "IN-PROCESS"
+            } else {
+                "NEEDS-ACTION"
+            };
+            vec![
+                String::from("BEGIN:VTODO"),
+                uid,
+                format!("DTSTAMP:{}", timestamp(now)),
+                format!("CREATED:{}", timestamp(item.created)),
+                format!("DUE;VALUE=DATE:{}", deadline.format("%Y%m%d")),
+                summary,
+                description,
+                format!("PERCENT-COMPLETE:{}", item.percentage.min(100)),
+                format!("STATUS:{status}"),
+                String::from("END:VTODO"),
+            ]
+        }
+        (_, None) => Vec::new(),
+    }
+}
+
+/// Renders the deadlines of Items with a day limit as an iCalendar file.
+///
+/// One-off Items become VTODOs due at `created + daylimit`, recurring Items become VEVENTs
+/// repeating every `daylimit` days. Items without a day limit have no date and are left out.
+/// UIDs only depend on the Item id, so importing the file again updates existing entries.
+pub fn to_ics(topics: &[(Topic, Vec<Item>)]) -> String {
+    let now = Utc::now();
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//finish-it//finish-it//EN"),
+        String::from("CALSCALE:GREGORIAN"),
+    ];
+    for (_, items) in topics {
+        for item in items {
+            lines.extend(item_lines(item, now));
+        }
+    }
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// Joins folded lines back together, as calendar apps read them.
+    fn unfold(text: &str) -> String {
+        text.replace("\r\n ", "")
+    }
+
+    #[test]
+    fn escapes_text_values() {
+        assert_eq!(escape("Plain text"), "Plain text");
+        assert_eq!(escape("one\r\ntwo\nthree"), "one\\ntwo\\nthree");
+        assert_eq!(
+            escape("a;b,c\\d\ne"),
+            "a\\;b\\,c\\\\d\\ne",
+            "backslashes are escaped before the escapes are added"
+        );
+    }
+
+    #[test]
+    fn folds_long_lines_at_75_octets() {
+        assert_eq!(fold("SUMMARY:short"), "SUMMARY:short\r\n");
+
+        for line in [
+            format!("SUMMARY:{}", "x".repeat(200)),
+            format!("SUMMARY:{}", "é".repeat(100)),
+            format!("SUMMARY:{}", "📚".repeat(40)),
+        ] {
+            let folded = fold(&line);
+            assert!(folded.ends_with("\r\n"));
+            let physical: Vec<&str> = folded.trim_end_matches("\r\n").split("\r\n").collect();
+            assert!(physical.len() > 1);
+            assert!(physical.iter().all(|l| l.len() <= 75), "{physical:?}");
+            assert!(physical[1..].iter().all(|l| l.starts_with(' ')));
+            assert_eq!(unfold(&folded), format!("{line}\r\n"));
+        }
+    }
+
+    #[test]
+    fn exports_only_items_with_a_day_limit() {
+        let created = Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap();
+        let item = |id, name: &str, isrecurring, percentage, daylimit| Item {
+            id,
+            name: name.to_string(),
+            topicname: String::from("Home, Garden"),
+            isrecurring,
+            percentage,
+            daylimit,
+            created,
+            ..Item::default()
+        };
+        let topic = Topic {
+            name: String::from("Home, Garden"),
+            ..Topic::default()
+        };
+        let ics = to_ics(&[(
+            topic,
+            vec![
+                item(1, "Paint; fence", 0, 100, 10),
+                item(2, "Water plants", 1, 20, 3),
+                item(3, "Someday", 0, 0, 0),
+            ],
+        )]);
+        let ics = unfold(&ics);
+
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("SUMMARY:Home\\, Garden: Paint\\; fence\r\n"));
+        assert!(ics.contains("DUE;VALUE=DATE:20260311\r\n"));
+        assert!(ics.contains("STATUS:COMPLETED\r\n"));
+        assert!(ics.contains("UID:finish-it-item-2\r\nDTSTAMP:"));
+        assert!(ics.contains("DTSTART;VALUE=DATE:20260301\r\nRRULE:FREQ=DAILY;INTERVAL=3\r\n"));
+        assert!(!ics.contains("Someday"));
+        assert_eq!(ics.matches("BEGIN:VTODO").count(), 1);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+    }
+}
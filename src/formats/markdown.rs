@@ -0,0 +1,120 @@
+//! Markdown export, one section per Topic.
+
+use std::fmt::Write;
+
+use super::item_details;
+use crate::model::{Item, Topic};
+
+/// Escapes the characters that would start emphasis, links, code, tables, HTML or entities,
+/// so names show up as they were typed.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~' | '!' | '&'
+        ) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Renders Topics as Markdown sections with one task list entry per Item.
+pub fn to_markdown(topics: &[(Topic, Vec<Item>)], bar_width: usize) -> String {
+    let mut out = String::from("# Finish It!\n");
+    for (topic, items) in topics {
+        let _ = write!(out, "\n## {}\n\n", escape(&topic.name));
+        if items.is_empty() {
+            out.push_str("_No items yet._\n");
+        }
+        for item in items {
+            let checkbox = if item.is_done() { "x" } else { " " };
+            let _ = writeln!(
+                out,
+                "- [{}] {} `{}` {}",
+                checkbox,
+                escape(&item.name),
+                item.get_dot_vec(bar_width),
+                item_details(item)
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, percentage: u8) -> Item {
+        Item {
+            name: name.to_string(),
+            topicname: String::from("Books *and* _papers_"),
+            percentage,
+            ..Item::default()
+        }
+    }
+
+    fn unescape(text: &str) -> String {
+        let mut out = String::new();
+        let mut chars = text.chars();
+        while let Some(c) = chars.next() {
+            out.push(if c == '\\' { chars.next().unwrap() } else { c });
+        }
+        out
+    }
+
+    /// The names written in the task list, read back the way a Markdown renderer shows them.
+    fn names(markdown: &str) -> Vec<String> {
+        markdown
+            .lines()
+            .filter_map(|line| line.strip_prefix("- [x] ").or(line.strip_prefix("- [ ] ")))
+            .map(|entry| unescape(&entry[..entry.find(" `").unwrap()]))
+            .collect()
+    }
+
+    #[test]
+    fn names_survive_a_round_trip() {
+        let items = vec![
+            item("Read SICP", 40),
+            item("Read TAOCP [vol 1](http://x)", 100),
+            item("a*b*c and _d_ | e # f", 0),
+            item("`code` <b>&amp;</b> ~~gone~~ ![img] \\", 5),
+            item("Ünïcødé 📚", 99),
+        ];
+        let topic = Topic {
+            name: String::from("Books *and* _papers_"),
+            ..Topic::default()
+        };
+        let markdown = to_markdown(&[(topic, items.clone())], 10);
+        assert!(markdown.contains("\n## Books \\*and\\* \\_papers\\_\n"));
+        assert!(markdown.contains("- [ ] Read SICP `"));
+        assert!(markdown.contains("- [x] Read TAOCP \\[vol 1\\](http://x) `"));
+        let expected: Vec<String> = items.into_iter().map(|item| item.name).collect();
+        assert_eq!(names(&markdown), expected);
+    }
+
+    #[test]
+    fn markdown_punctuation_is_escaped() {
+        assert_eq!(escape("a|b"), "a\\|b");
+        assert_eq!(escape("#1 [x]"), "\\#1 \\[x\\]");
+        assert_eq!(escape("*_`"), "\\*\\_\\`");
+        assert_eq!(escape("<&>"), "\\<\\&\\>");
+        assert_eq!(escape("C:\\tmp"), "C:\\\\tmp");
+        assert_eq!(escape("plain words, 50%."), "plain words, 50%.");
+    }
+
+    #[test]
+    fn empty_topics_say_so() {
+        let topic = Topic {
+            name: String::from("Films"),
+            ..Topic::default()
+        };
+        assert_eq!(
+            to_markdown(&[(topic, Vec::new())], 10),
+            "# Finish It!\n\n## Films\n\n_No items yet._\n"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+//! Exporting Topics and Items to other tools' file formats and importing them back.
+
+use crate::error::StoreError;
+use crate::model::{Item, Topic};
+use crate::store::Store;
+
+pub mod html;
+pub mod ics;
+pub mod markdown;
+pub mod org;
+pub mod taskwarrior;
+pub mod todotxt;
+
+/// Reads every Topic with its Items, or only the Topic called `topic_name` if given. A
+/// `topic_name` no Topic has is an error rather than an empty export.
+pub fn collect_topics(
+    store: &dyn Store,
+    topic_name: Option<&str>,
+) -> Result<Vec<(Topic, Vec<Item>)>, StoreError> {
+    let mut topics = Vec::new();
+    for topic in store.topics()? {
+        if topic_name.is_some_and(|name| name != topic.name) {
+            continue;
+        }
+        let items = store.items(&topic.name)?;
+        topics.push((topic, items));
+    }
+    match topic_name {
+        Some(name) if topics.is_empty() => Err(StoreError::NoSuchTopic(name.to_owned())),
+        _ => Ok(topics),
+    }
+}
+
+/// Short description of an Item's state shared by the text based exporters.
+fn item_details(item: &Item) -> String {
+    let mut details = format!("{}%", item.percentage);
+    if item.isrecurring == 1 {
+        details.push_str(&format!(
+            ", recurring, finished {} times",
+            item.timesfinished
+        ));
+    }
+    if item.daylimit > 0 {
+        details.push_str(&format!(", {} days left", item.days_left()));
+    }
+    details
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStore;
+
+    fn store() -> MemoryStore {
+        let store = MemoryStore::new();
+        for (name, topicname) in [("SICP", "Books"), ("Heat", "Films"), ("TAOCP", "Books")] {
+            let item = Item {
+                name: name.to_string(),
+                topicname: topicname.to_string(),
+                ..Item::default()
+            };
+            store.add_item(&item).unwrap();
+        }
+        store
+    }
+
+    #[test]
+    fn collects_every_topic_or_only_the_one_asked_for() {
+        let store = store();
+        let all = collect_topics(&store, None).unwrap();
+        let names: Vec<_> = all
+            .iter()
+            .map(|(topic, items)| (topic.name.as_str(), items.len()))
+            .collect();
+        assert_eq!(names, [("Books", 2), ("Films", 1)]);
+
+        let films = collect_topics(&store, Some("Films")).unwrap();
+        assert_eq!(films.len(), 1);
+        assert_eq!(films[0].1[0].name, "Heat");
+    }
+
+    #[test]
+    fn unknown_topics_are_an_error() {
+        let err = collect_topics(&store(), Some("Film")).unwrap_err();
+        assert!(matches!(&err, StoreError::NoSuchTopic(name) if name == "Film"));
+        assert_eq!(err.to_string(), "There is no Topic called 'Film'.");
+    }
+}
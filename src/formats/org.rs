@@ -0,0 +1,222 @@
+//! Org mode export and import.
+
+use std::fmt::Write;
+
+use chrono::prelude::*;
+
+use crate::model::{Item, Topic};
+
+/// Renders Topics as top level Org headings and their Items as TODO/DONE subheadings.
+///
+/// Progress is written as a `[n%]` cookie, the day limit as a `DEADLINE:` and the remaining
+/// fields in a `:PROPERTIES:` drawer, so `from_org` gets back exactly the same Items.
+pub fn to_org(topics: &[(Topic, Vec<Item>)]) -> String {
+    let mut out = String::new();
+    for (topic, items) in topics {
+        let _ = writeln!(out, "* {}", topic.name);
+        for item in items {
+            let keyword = if item.is_done() { "DONE" } else { "TODO" };
+            let _ = writeln!(out, "** {} {} [{}%]", keyword, item.name, item.percentage);
+            if let Some(deadline) = item.deadline() {
+                let _ = writeln!(out, "   DEADLINE: <{}>", deadline.format("%Y-%m-%d %a"));
+            }
+            let _ = writeln!(out, "   :PROPERTIES:");
+            let _ = writeln!(out, "   :ID:            {}", item.id);
+            let _ = writeln!(out, "   :ISRECURRING:   {}", item.isrecurring);
+            let _ = writeln!(out, "   :TIMESFINISHED: {}", item.timesfinished);
+            let _ = writeln!(
+                out,
+                "   :CREATED:       {}",
+                item.created.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            );
+            let _ = writeln!(out, "   :END:");
+        }
+    }
+    out
+}
+
+/// Splits `Name [40%]` into the name and the progress cookie.
+fn split_cookie(title: &str) -> (&str, Option<u8>) {
+    let title = title.trim_end();
+    if let Some(start) = title.rfind(" [") {
+        let cookie = &title[start + 2..];
+        if let Some(percentage) = cookie.strip_suffix("%]").and_then(|p| p.parse::<u8>().ok()) {
+            return (title[..start].trim_end(), Some(percentage));
+        }
+    }
+    (title, None)
+}
+
+fn parse_item(topic: &str, title: &str, body: &[&str]) -> Result<Item, String> {
+    let err = |what: &str| format!("invalid {what} in Org heading '{title}'");
+
+    let (done, title) = match title.split_once(' ') {
+        Some(("DONE", rest)) => (true, rest),
+        Some(("TODO", rest)) => (false, rest),
+        _ => (false, title),
+    };
+    let (name, cookie) = split_cookie(title);
+
+    let mut item = Item {
+        name: name.to_string(),
+        topicname: topic.to_string(),
+        percentage: cookie.unwrap_or(if done { 100 } else { 0 }),
+        timesfinished: done as usize,
+        created: Utc::now(),
+        ..Item::default()
+    };
+    let mut deadline = None;
+    for line in body.iter().map(|line| line.trim()) {
+        if let Some(date) = line.strip_prefix("DEADLINE: <") {
+            let date = date.get(..10).ok_or_else(|| err("DEADLINE"))?;
+            deadline =
+                Some(NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| err("DEADLINE"))?);
+            continue;
+        }
+        let property = line
+            .strip_prefix(':')
+            .and_then(|l| l.split_once(':'))
+            .map(|(key, value)| (key.to_uppercase(), value.trim()));
+        match property {
+            Some((key, v)) if key == "ID" => item.id = v.parse().map_err(|_| err("ID"))?,
+            Some((key, v)) if key == "ISRECURRING" => {
+                item.isrecurring = v.parse().map_err(|_| err("ISRECURRING"))?
+            }
+            Some((key, v)) if key == "TIMESFINISHED" => {
+                item.timesfinished = v.parse().map_err(|_| err("TIMESFINISHED"))?
+            }
+            Some((key, v)) if key == "CREATED" => {
+                item.created = DateTime::parse_from_rfc3339(v)
+                    .map_err(|_| err("CREATED"))?
+                    .with_timezone(&Utc)
+            }
+            _ => {}
+        }
+    }
+    if let Some(deadline) = deadline {
+        item.daylimit = (deadline - item.created.date_naive()).num_days().max(1) as usize;
+    }
+    Ok(item)
+}
+
+/// Parses Org headings written by `to_org`, or by hand, into Items.
+///
+/// Second level headings become Items of the first level heading above them.
+/// Deeper headings and any other text are ignored.
+pub fn from_org(content: &str) -> Result<Vec<Item>, String> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut items = Vec::new();
+    let mut topic: Option<&str> = None;
+
+    for (i, line) in lines.iter().enumerate() {
+        if let Some(name) = line.strip_prefix("* ") {
+            topic = Some(name.trim());
+        } else if let Some(title) = line.strip_prefix("** ") {
+            let topic =
+                topic.ok_or_else(|| format!("Org heading '{title}' has no Topic above it"))?;
+            let body_end = lines[i + 1..]
+                .iter()
+                .position(|l| l.starts_with('*'))
+                .map_or(lines.len(), |end| i + 1 + end);
+            items.push(parse_item(topic, title.trim(), &lines[i + 1..body_end])?);
+        }
+    }
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: usize, name: &str, isrecurring: u8, percentage: u8, daylimit: usize) -> Item {
+        Item {
+            id,
+            name: name.to_string(),
+            topicname: String::from("Books"),
+            isrecurring,
+            percentage,
+            timesfinished: (isrecurring == 0 && percentage == 100) as usize,
+            daylimit,
+            created: Utc.with_ymd_and_hms(2026, 3, 1, 12, 30, 15).unwrap(),
+        }
+    }
+
+    #[test]
+    fn items_survive_a_round_trip() {
+        let items = vec![
+            item(1, "Read SICP", 0, 40, 10),
+            item(2, "Read TAOCP [vol 1]", 0, 100, 0),
+            item(3, "TODO list [50%]", 0, 0, 1),
+            item(4, "DONE deal", 0, 5, 0),
+            item(5, "Ünïcødé 📚", 0, 99, 365),
+            Item {
+                timesfinished: 12,
+                ..item(6, "Run", 1, 30, 2)
+            },
+        ];
+        let topic = Topic {
+            name: String::from("Books"),
+            ..Topic::default()
+        };
+        let org = to_org(&[(topic, items.clone())]);
+        assert!(org.starts_with("* Books\n** TODO Read SICP [40%]\n"));
+        assert!(org.contains("** DONE Read TAOCP [vol 1] [100%]\n"));
+        assert_eq!(from_org(&org), Ok(items));
+    }
+
+    #[test]
+    fn parses_hand_written_headings() {
+        let items = from_org(
+            "#+TITLE: Reading\n\
+             * Books\n\
+             ** TODO Read SICP [40%]\n   \
+                DEADLINE: <2026-03-10 Tue>\n   \
+                :properties:\n   \
+                :created: 2026-03-01T00:00:00Z\n   \
+                :end:\n\
+             *** Notes on chapter 1\n\
+             ** DONE Read TAOCP\n\
+             ** Plain heading\n\
+             Some text.\n\
+             * Garden\n\
+             ** Water plants [7%]\n",
+        )
+        .unwrap();
+
+        let names: Vec<(&str, &str, u8, usize)> = items
+            .iter()
+            .map(|item| {
+                let name = item.name.as_str();
+                (
+                    item.topicname.as_str(),
+                    name,
+                    item.percentage,
+                    item.timesfinished,
+                )
+            })
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("Books", "Read SICP", 40, 0),
+                ("Books", "Read TAOCP", 100, 1),
+                ("Books", "Plain heading", 0, 0),
+                ("Garden", "Water plants", 7, 0),
+            ]
+        );
+        assert_eq!(items[0].daylimit, 9);
+        assert_eq!(items[1].daylimit, 0);
+        assert_eq!(items[0].id, 0);
+    }
+
+    #[test]
+    fn rejects_invalid_headings() {
+        assert!(from_org("** TODO No Topic").is_err());
+        assert!(from_org("* Books\n** Read\n   :ID: seven\n").is_err());
+        assert!(from_org("* Books\n** Read\n   DEADLINE: <2026-13-01>\n").is_err());
+        assert!(from_org("* Books\n** Read\n   DEADLINE: <2026>\n").is_err());
+        assert!(from_org("* Books\n** Read\n   DEADLINE: <2026-03-0é>\n").is_err());
+        assert!(from_org("* Books\n** Read\n   :CREATED: yesterday\n").is_err());
+        assert_eq!(from_org(""), Ok(Vec::new()));
+    }
+}
@@ -0,0 +1,295 @@
+//! Taskwarrior JSON export and import.
+
+use chrono::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Item, Topic};
+
+const DATE_FORMAT: &str = "%Y%m%dT%H%M%SZ";
+
+/// A task as written by `task export` and read by `task import`.
+///
+/// Fields Taskwarrior has no attribute for are kept in `fit*` user defined attributes.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct Task {
+    uuid: String,
+    description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    project: Option<String>,
+    status: String,
+    entry: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    due: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    recur: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fitid: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fitpercentage: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fittimesfinished: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    fitrecurring: Option<u8>,
+}
+
+/// Derives a fixed UUID from the Item id so exporting twice yields the same task.
+fn item_uuid(id: usize) -> String {
+    format!("6669742d-0000-4000-8000-{id:012x}")
+}
+
+fn parse_date(date: &str) -> Result<DateTime<Utc>, String> {
+    NaiveDateTime::parse_from_str(date, DATE_FORMAT)
+        .map(|d| Utc.from_utc_datetime(&d))
+        .map_err(|_| format!("invalid Taskwarrior date '{date}'"))
+}
+
+fn to_task(item: &Item) -> Task {
+    let now = Utc::now();
+    let recur = match (item.isrecurring, item.daylimit) {
+        (1, n) if n > 0 => Some(format!("{n}d")),
+        _ => None,
+    };
+    let status = match (item.is_done(), &recur) {
+        (true, _) => "completed",
+        (false, Some(_)) => "recurring",
+        (false, None) => "pending",
+    };
+    Task {
+        uuid: item_uuid(item.id),
+        description: item.name.to_owned(),
+        project: Some(item.topicname.to_owned()),
+        status: status.to_string(),
+        entry: item.created.format(DATE_FORMAT).to_string(),
+        due: item.deadline().map(|d| d.format(DATE_FORMAT).to_string()),
+        end: item.is_done().then(|| now.format(DATE_FORMAT).to_string()),
+        recur,
+        fitid: Some(item.id),
+        fitpercentage: Some(item.percentage),
+        fittimesfinished: Some(item.timesfinished),
+        fitrecurring: Some(item.isrecurring),
+    }
+}
+
+fn from_task(task: Task) -> Result<Item, String> {
+    let completed = task.status == "completed";
+    let isrecurring = task
+        .fitrecurring
+        .unwrap_or((task.status == "recurring" || task.recur.is_some()) as u8);
+    let created = parse_date(&task.entry)?;
+    let daylimit = match &task.due {
+        Some(due) => (parse_date(due)? - created).num_days().max(1) as usize,
+        None => 0,
+    };
+    // Taskwarrior keeps the uuid across edits but gives copies (`task duplicate`) a new one, so
+    // only a task with the uuid we exported is the Item `fitid` names.
+    let id = task
+        .fitid
+        .filter(|&id| task.uuid == item_uuid(id))
+        .unwrap_or(0);
+    Ok(Item {
+        id,
+        name: task.description,
+        topicname: task.project.unwrap_or_else(|| String::from("Inbox")),
+        isrecurring,
+        percentage: task
+            .fitpercentage
+            .unwrap_or(if completed { 100 } else { 0 }),
+        timesfinished: task.fittimesfinished.unwrap_or(completed as usize),
+        daylimit,
+        created,
+    })
+}
+
+/// Renders Items as a JSON array in the format of `task export`.
+pub fn to_taskwarrior(topics: &[(Topic, Vec<Item>)]) -> Result<String, serde_json::Error> {
+    let tasks: Vec<Task> = topics
+        .iter()
+        .flat_map(|(_, items)| items.iter().map(to_task))
+        .collect();
+    serde_json::to_string_pretty(&tasks)
+}
+
+/// Parses the output of `task export`, either a JSON array or one task object per line.
+/// Deleted tasks are skipped.
+pub fn from_taskwarrior(content: &str) -> Result<Vec<Item>, String> {
+    let tasks: Vec<Task> = match serde_json::from_str(content) {
+        Ok(tasks) => tasks,
+        Err(_) => content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| serde_json::from_str(line.trim_end_matches(',')))
+            .collect::<Result<_, _>>()
+            .map_err(|err| format!("invalid Taskwarrior export: {err}"))?,
+    };
+    tasks
+        .into_iter()
+        .filter(|task| task.status != "deleted")
+        .map(from_task)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: usize, isrecurring: u8, percentage: u8, daylimit: usize) -> Item {
+        Item {
+            id,
+            name: String::from("Read SICP"),
+            topicname: String::from("Books"),
+            isrecurring,
+            percentage,
+            timesfinished: (isrecurring == 0 && percentage == 100) as usize,
+            daylimit,
+            created: Utc.with_ymd_and_hms(2026, 3, 1, 12, 30, 15).unwrap(),
+        }
+    }
+
+    fn task(json: &str) -> Task {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn items_survive_a_round_trip() {
+        let items = vec![
+            item(1, 0, 40, 10),
+            item(2, 0, 100, 0),
+            Item {
+                timesfinished: 12,
+                ..item(3, 1, 30, 2)
+            },
+            item(4, 1, 0, 0),
+        ];
+        let topic = Topic {
+            name: String::from("Books"),
+            ..Topic::default()
+        };
+        let json = to_taskwarrior(&[(topic, items.clone())]).unwrap();
+        assert_eq!(from_taskwarrior(&json), Ok(items));
+    }
+
+    #[test]
+    fn items_map_to_task_attributes() {
+        let pending = to_task(&item(1, 0, 40, 10));
+        assert_eq!(pending.uuid, "6669742d-0000-4000-8000-000000000001");
+        assert_eq!(pending.status, "pending");
+        assert_eq!(pending.entry, "20260301T123015Z");
+        assert_eq!(pending.due.as_deref(), Some("20260311T123015Z"));
+        assert_eq!((pending.recur, pending.end), (None, None));
+
+        let done = to_task(&item(255, 0, 100, 0));
+        assert_eq!(done.uuid, "6669742d-0000-4000-8000-0000000000ff");
+        assert_eq!(done.status, "completed");
+        assert!(done.end.is_some());
+        assert_eq!(done.due, None);
+
+        let habit = to_task(&item(3, 1, 100, 2));
+        assert_eq!(habit.status, "recurring");
+        assert_eq!(habit.recur.as_deref(), Some("2d"));
+        assert_eq!(habit.end, None);
+
+        // Without a day limit Taskwarrior has no period to repeat at.
+        assert_eq!(to_task(&item(4, 1, 0, 0)).status, "pending");
+    }
+
+    #[test]
+    fn fit_attributes_win_over_the_status() {
+        let task = task(
+            r#"{"uuid":"6669742d-0000-4000-8000-000000000007","description":"Run","status":"completed",
+            "entry":"20260301T123015Z","fitid":7,"fitpercentage":30,"fittimesfinished":12,"fitrecurring":1}"#,
+        );
+        let item = from_task(task).unwrap();
+        assert_eq!(
+            (
+                item.id,
+                item.isrecurring,
+                item.percentage,
+                item.timesfinished
+            ),
+            (7, 1, 30, 12)
+        );
+        assert_eq!(item.topicname, "Inbox");
+    }
+
+    #[test]
+    fn tasks_from_taskwarrior_get_defaults() {
+        let done = from_task(task(
+            r#"{"uuid":"a","description":"Call mum","project":"Family","status":"completed","entry":"20260301T123015Z"}"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            (
+                done.id,
+                done.isrecurring,
+                done.percentage,
+                done.timesfinished
+            ),
+            (0, 0, 100, 1)
+        );
+        assert_eq!(done.topicname, "Family");
+
+        let recurring = from_task(task(
+            r#"{"uuid":"b","description":"Water plants","status":"recurring","entry":"20260301T123015Z",
+            "due":"20260303T123015Z"}"#,
+        ))
+        .unwrap();
+        assert_eq!(
+            (
+                recurring.isrecurring,
+                recurring.percentage,
+                recurring.timesfinished
+            ),
+            (1, 0, 0)
+        );
+        assert_eq!(recurring.daylimit, 2);
+
+        let recur_only = from_task(task(
+            r#"{"uuid":"c","description":"Stretch","status":"pending","entry":"20260301T123015Z","recur":"daily"}"#,
+        ))
+        .unwrap();
+        assert_eq!(recur_only.isrecurring, 1);
+
+        let bad_date =
+            task(r#"{"uuid":"d","description":"x","status":"pending","entry":"yesterday"}"#);
+        assert_eq!(
+            from_task(bad_date),
+            Err(String::from("invalid Taskwarrior date 'yesterday'"))
+        );
+    }
+
+    #[test]
+    fn only_the_exported_uuid_keeps_the_id() {
+        let exported = r#"{"uuid":"6669742d-0000-4000-8000-000000000007","description":"Renamed",
+            "status":"pending","entry":"20260301T123015Z","fitid":7}"#;
+        assert_eq!(from_task(task(exported)).unwrap().id, 7);
+
+        // `task duplicate` copies the attributes but not the uuid.
+        let copy = r#"{"uuid":"0f1c2d3e-4b5a-4c6d-8e7f-000000000001","description":"Renamed",
+            "status":"pending","entry":"20260301T123015Z","fitid":7}"#;
+        assert_eq!(from_task(task(copy)).unwrap().id, 0);
+    }
+
+    #[test]
+    fn deleted_tasks_are_skipped_and_lines_are_read_one_by_one() {
+        let lines = r#"{"uuid":"a","description":"Keep","status":"pending","entry":"20260301T123015Z"},
+{"uuid":"b","description":"Gone","status":"deleted","entry":"20260301T123015Z"}
+
+{"uuid":"c","description":"Done","status":"completed","entry":"20260301T123015Z"}
+"#;
+        let names: Vec<String> = from_taskwarrior(lines)
+            .unwrap()
+            .into_iter()
+            .map(|item| item.name)
+            .collect();
+        assert_eq!(names, ["Keep", "Done"]);
+
+        let array = format!("[{}]", lines.replace("}\n\n{", "},{"));
+        assert_eq!(from_taskwarrior(&array).unwrap().len(), 2);
+
+        assert!(from_taskwarrior("not json")
+            .unwrap_err()
+            .starts_with("invalid Taskwarrior export: "));
+    }
+}
@@ -0,0 +1,332 @@
+//! todo.txt export and import.
+
+use chrono::prelude::*;
+
+use crate::model::{Item, Topic};
+
+/// Keys of the `key:value` tags Items are exported with.
+const KEYS: [&str; 6] = ["due", "pct", "finished", "recurring", "created", "id"];
+
+/// Topic names become `+project` tags, which can not contain spaces. Spaces are written as `_`,
+/// and `_` and `\` are escaped with a `\` so `topic_name` can tell them apart.
+fn project_tag(topic: &str) -> String {
+    let mut tag = String::from("+");
+    for c in topic.chars() {
+        match c {
+            ' ' => tag.push('_'),
+            '_' | '\\' => {
+                tag.push('\\');
+                tag.push(c);
+            }
+            c => tag.push(c),
+        }
+    }
+    tag
+}
+
+/// The Topic name of a `+project` tag without its `+`, see `project_tag`.
+fn topic_name(project: &str) -> String {
+    let mut name = String::new();
+    let mut chars = project.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => name.extend(chars.next()),
+            '_' => name.push(' '),
+            c => name.push(c),
+        }
+    }
+    name
+}
+
+/// Whether `word` would be read as a `+project` or one of our `key:value` tags.
+fn is_tag(word: &str) -> bool {
+    word.strip_prefix('+').is_some_and(|p| !p.is_empty())
+        || word
+            .split_once(':')
+            .is_some_and(|(key, _)| KEYS.contains(&key))
+}
+
+/// Item names are written as they are, except for words that would be read as tags. Those, and
+/// words starting with `\`, get a `\` in front that importing takes off again.
+fn escape_name(name: &str) -> String {
+    let mut out = String::new();
+    let mut rest = name;
+    while let Some(start) = rest.find(|c: char| !c.is_whitespace()) {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let word = &rest[..end];
+        if is_tag(word) || word.starts_with('\\') {
+            out.push('\\');
+        }
+        out.push_str(word);
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Renders Items as todo.txt lines.
+///
+/// Finished one-off Items are marked completed. Everything todo.txt has no field for is kept
+/// in `key:value` tags: `pct`, `finished`, `recurring`, `created` and `id`.
+pub fn to_todotxt(topics: &[(Topic, Vec<Item>)]) -> String {
+    let today = Local::now().date_naive();
+    let mut out = String::new();
+    for (topic, items) in topics {
+        for item in items {
+            let mut parts = Vec::new();
+            if item.is_done() {
+                parts.push(format!("x {today}"));
+            }
+            parts.push(item.created.date_naive().to_string());
+            parts.push(escape_name(&item.name));
+            parts.push(project_tag(&topic.name));
+            if let Some(deadline) = item.deadline() {
+                parts.push(format!("due:{}", deadline.date_naive()));
+            }
+            parts.push(format!("pct:{}", item.percentage));
+            parts.push(format!("finished:{}", item.timesfinished));
+            if item.isrecurring == 1 {
+                parts.push(String::from("recurring:1"));
+            }
+            parts.push(format!(
+                "created:{}",
+                item.created.to_rfc3339_opts(SecondsFormat::Secs, true)
+            ));
+            parts.push(format!("id:{}", item.id));
+            out.push_str(&parts.join(" "));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Words of `line` with the byte offset each starts at.
+fn words(line: &str) -> Vec<(usize, &str)> {
+    let mut words = Vec::new();
+    let mut start = None;
+    for (i, c) in line.char_indices() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some(i),
+            (true, Some(s)) => {
+                words.push((s, &line[s..i]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        words.push((s, &line[s..]));
+    }
+    words
+}
+
+fn parse_line(line: &str) -> Result<Item, String> {
+    let words = words(line);
+    let mut words = words.as_slice();
+    let err = |what: &str| format!("invalid {what} in todo.txt line '{line}'");
+
+    let completed = words.first().is_some_and(|(_, word)| *word == "x");
+    if completed {
+        words = &words[1..];
+    }
+    if words
+        .first()
+        .is_some_and(|(_, t)| t.len() == 3 && t.starts_with('(') && t.ends_with(')'))
+    {
+        words = &words[1..];
+    }
+    // A completed task has its completion date first, then its creation date.
+    let mut dates = Vec::new();
+    while let Some(date) = words
+        .first()
+        .filter(|_| dates.len() < 1 + completed as usize)
+        .and_then(|(_, t)| NaiveDate::parse_from_str(t, "%Y-%m-%d").ok())
+    {
+        dates.push(date);
+        words = &words[1..];
+    }
+    let creation_date = match (completed, dates.as_slice()) {
+        (true, [_, created]) | (false, [created]) => Some(*created),
+        _ => None,
+    };
+
+    let mut item = Item {
+        percentage: if completed { 100 } else { 0 },
+        timesfinished: completed as usize,
+        created: creation_date
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| Utc.from_utc_datetime(&d))
+            .unwrap_or_else(Utc::now),
+        ..Item::default()
+    };
+    let mut name = String::new();
+    // End of the last word that went into the name, to keep the spacing between name words.
+    let mut name_end = None;
+    let mut due = None;
+    for (i, &(start, token)) in words.iter().enumerate() {
+        if let Some(project) = token.strip_prefix('+').filter(|p| !p.is_empty()) {
+            if item.topicname.is_empty() {
+                item.topicname = topic_name(project);
+            }
+            continue;
+        }
+        match token.split_once(':') {
+            Some(("pct", v)) => item.percentage = v.parse().map_err(|_| err("pct"))?,
+            Some(("finished", v)) => item.timesfinished = v.parse().map_err(|_| err("finished"))?,
+            Some(("recurring", v)) => item.isrecurring = v.parse().map_err(|_| err("recurring"))?,
+            Some(("id", v)) => item.id = v.parse().map_err(|_| err("id"))?,
+            Some(("created", v)) => {
+                item.created = DateTime::parse_from_rfc3339(v)
+                    .map_err(|_| err("created"))?
+                    .with_timezone(&Utc)
+            }
+            Some(("due", v)) => {
+                due = Some(NaiveDate::parse_from_str(v, "%Y-%m-%d").map_err(|_| err("due"))?)
+            }
+            _ => {
+                match name_end {
+                    Some((end, last)) if last + 1 == i => name.push_str(&line[end..start]),
+                    Some(_) => name.push(' '),
+                    None => {}
+                }
+                name.push_str(token.strip_prefix('\\').unwrap_or(token));
+                name_end = Some((start + token.len(), i));
+            }
+        }
+    }
+
+    if let Some(due) = due {
+        let days = (due - item.created.date_naive()).num_days();
+        item.daylimit = days.max(1) as usize;
+    }
+    if item.topicname.is_empty() {
+        item.topicname = String::from("Inbox");
+    }
+    item.name = name;
+    if item.name.is_empty() {
+        return Err(err("description"));
+    }
+    Ok(item)
+}
+
+/// Parses todo.txt content into Items. Tasks without a `+project` go to the "Inbox" Topic.
+pub fn from_todotxt(content: &str) -> Result<Vec<Item>, String> {
+    content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(parse_line)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(topic: &str, items: Vec<Item>) -> String {
+        let topic = Topic {
+            name: topic.to_string(),
+            ..Topic::default()
+        };
+        to_todotxt(&[(topic, items)])
+    }
+
+    fn item(id: usize, name: &str, topic: &str) -> Item {
+        Item {
+            id,
+            name: name.to_string(),
+            topicname: topic.to_string(),
+            percentage: 30,
+            daylimit: 7,
+            created: Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap(),
+            ..Item::default()
+        }
+    }
+
+    #[test]
+    fn names_and_topics_survive_a_round_trip() {
+        let names = [
+            "Water due:soon",
+            "Pay  rent",
+            "Try +1",
+            "Check id:42 and pct:5",
+            "\\backslash and \\\\double",
+            "2026-01-01 review",
+            "x marks the spot",
+            "Plain",
+        ];
+        let topics = [
+            "Home_Office",
+            "My Garden",
+            "back\\slash",
+            "a_b c\\_d",
+            "+plus",
+        ];
+        for topic in topics {
+            let items: Vec<Item> = names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| item(i + 1, name, topic))
+                .collect();
+            let imported = from_todotxt(&export(topic, items.clone())).unwrap();
+            assert_eq!(imported.len(), items.len());
+            for (imported, item) in imported.iter().zip(&items) {
+                assert_eq!(imported.name, item.name, "{topic}");
+                assert_eq!(imported.topicname, item.topicname);
+                assert_eq!(imported.id, item.id);
+                assert_eq!(imported.percentage, 30);
+                assert_eq!(imported.daylimit, 7);
+                assert_eq!(imported.created, item.created);
+            }
+        }
+    }
+
+    #[test]
+    fn done_items_round_trip_as_completed_tasks() {
+        let done = Item {
+            percentage: 100,
+            timesfinished: 1,
+            ..item(3, "2026-02-02 taxes", "Admin")
+        };
+        let line = export("Admin", vec![done.clone()]);
+        assert!(line.starts_with("x "));
+        let imported = from_todotxt(&line).unwrap().remove(0);
+        assert_eq!(imported.name, done.name);
+        assert_eq!(imported.percentage, 100);
+        assert_eq!(imported.timesfinished, 1);
+        assert!(imported.is_done());
+    }
+
+    #[test]
+    fn parses_hand_written_lines() {
+        let items = from_todotxt(
+            "(A) 2026-03-01 Call  mom +Family_Stuff @phone due:2026-03-08\n\
+             \n\
+             x 2026-03-05 2026-03-01 Buy milk\n\
+             just a name url:http://example.com\n",
+        )
+        .unwrap();
+        assert_eq!(items.len(), 3);
+
+        assert_eq!(items[0].name, "Call  mom @phone");
+        assert_eq!(items[0].topicname, "Family Stuff");
+        assert_eq!(items[0].daylimit, 7);
+        assert_eq!(items[0].percentage, 0);
+
+        assert_eq!(items[1].name, "Buy milk");
+        assert_eq!(items[1].topicname, "Inbox");
+        assert_eq!(items[1].created.date_naive().to_string(), "2026-03-01");
+        assert!(items[1].is_done());
+
+        assert_eq!(items[2].name, "just a name url:http://example.com");
+        assert_eq!(items[2].id, 0);
+    }
+
+    #[test]
+    fn rejects_invalid_tags_and_empty_names() {
+        assert!(from_todotxt("Walk pct:lots").is_err());
+        assert!(from_todotxt("Walk due:tomorrow").is_err());
+        assert!(from_todotxt("2026-03-01 +Garden pct:10").is_err());
+    }
+}
@@ -0,0 +1,723 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::{ActiveBlock, ActivePopUp, MenuItem};
+
+/// Everything a key can be bound to. What directional actions do depends on the context.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    Quit,
+    GoHome,
+    GoTopics,
+    GoAdd,
+    GoActivity,
+    Review,
+    Profiles,
+    Up,
+    Down,
+    Left,
+    Right,
+    First,
+    Last,
+    Update,
+    Delete,
+    Increment,
+    Decrement,
+    FinishOnce,
+    Confirm,
+    Cancel,
+    Filter,
+    Range,
+    Help,
+    Suspend,
+}
+
+/// Where the user currently is, which decides the Actions keys can trigger.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyContext {
+    Home,
+    Topics,
+    Items,
+    Add,
+    Activity,
+    UpdatePopup,
+    DeletePopup,
+    ReviewPopup,
+    ProfilePopup,
+    Help,
+    ErrorPopup,
+}
+
+impl KeyContext {
+    pub fn from_state(menu: MenuItem, block: ActiveBlock, popup: ActivePopUp) -> Self {
+        match (popup, menu, block) {
+            (ActivePopUp::Update, _, _) => KeyContext::UpdatePopup,
+            (ActivePopUp::Delete, _, _) => KeyContext::DeletePopup,
+            (ActivePopUp::Review, _, _) => KeyContext::ReviewPopup,
+            (ActivePopUp::Profiles, _, _) => KeyContext::ProfilePopup,
+            (ActivePopUp::None, MenuItem::Home, _) => KeyContext::Home,
+            (ActivePopUp::None, MenuItem::Instances, ActiveBlock::Event) => KeyContext::Topics,
+            (ActivePopUp::None, MenuItem::Instances, ActiveBlock::InstanceBlock) => {
+                KeyContext::Items
+            }
+            (ActivePopUp::None, MenuItem::Add, _) => KeyContext::Add,
+            (ActivePopUp::None, MenuItem::Activity, _) => KeyContext::Activity,
+        }
+    }
+
+    fn is_popup(&self) -> bool {
+        matches!(
+            self,
+            KeyContext::UpdatePopup
+                | KeyContext::DeletePopup
+                | KeyContext::ReviewPopup
+                | KeyContext::ProfilePopup
+                | KeyContext::Help
+                | KeyContext::ErrorPopup
+        )
+    }
+
+    /// The context keys go to while the help overlay or the error popup covers the screen.
+    pub fn with_overlays(self, help: bool, error: bool) -> Self {
+        match (error, help) {
+            (true, _) => KeyContext::ErrorPopup,
+            (false, true) => KeyContext::Help,
+            (false, false) => self,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            KeyContext::Home => "Home",
+            KeyContext::Topics => "Topics",
+            KeyContext::Items => "Items",
+            KeyContext::Add => "Add",
+            KeyContext::Activity => "Activity",
+            KeyContext::UpdatePopup => "Update Progress",
+            KeyContext::DeletePopup => "Delete",
+            KeyContext::ReviewPopup => "Review",
+            KeyContext::ProfilePopup => "Profiles",
+            KeyContext::Help => "Help",
+            KeyContext::ErrorPopup => "Error",
+        }
+    }
+
+    const ALL: [KeyContext; 11] = [
+        KeyContext::Home,
+        KeyContext::Topics,
+        KeyContext::Items,
+        KeyContext::Add,
+        KeyContext::Activity,
+        KeyContext::UpdatePopup,
+        KeyContext::DeletePopup,
+        KeyContext::ReviewPopup,
+        KeyContext::ProfilePopup,
+        KeyContext::Help,
+        KeyContext::ErrorPopup,
+    ];
+}
+
+impl Action {
+    pub const ALL: [Action; 24] = [
+        Action::Quit,
+        Action::GoHome,
+        Action::GoTopics,
+        Action::GoAdd,
+        Action::GoActivity,
+        Action::Review,
+        Action::Profiles,
+        Action::Up,
+        Action::Down,
+        Action::Left,
+        Action::Right,
+        Action::First,
+        Action::Last,
+        Action::Update,
+        Action::Delete,
+        Action::Increment,
+        Action::Decrement,
+        Action::FinishOnce,
+        Action::Confirm,
+        Action::Cancel,
+        Action::Filter,
+        Action::Range,
+        Action::Help,
+        Action::Suspend,
+    ];
+
+    /// The Action switching to `tab`.
+    pub fn go_to(tab: MenuItem) -> Action {
+        match tab {
+            MenuItem::Home => Action::GoHome,
+            MenuItem::Instances => Action::GoTopics,
+            MenuItem::Add => Action::GoAdd,
+            MenuItem::Activity => Action::GoActivity,
+        }
+    }
+
+    /// Name of the Action in `[keys.bindings]`.
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::GoHome => "go_home",
+            Action::GoTopics => "go_topics",
+            Action::GoAdd => "go_add",
+            Action::GoActivity => "go_activity",
+            Action::Review => "review",
+            Action::Profiles => "profiles",
+            Action::Up => "up",
+            Action::Down => "down",
+            Action::Left => "left",
+            Action::Right => "right",
+            Action::First => "first",
+            Action::Last => "last",
+            Action::Update => "update",
+            Action::Delete => "delete",
+            Action::Increment => "increment",
+            Action::Decrement => "decrement",
+            Action::FinishOnce => "finish_once",
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::Filter => "filter",
+            Action::Range => "range",
+            Action::Help => "help",
+            Action::Suspend => "suspend",
+        }
+    }
+
+    /// What the Action does in `context`, shown in the help overlay and the footer.
+    pub fn description(&self, context: KeyContext) -> &'static str {
+        match (self, context) {
+            (Action::Quit, _) => "quit",
+            (Action::Suspend, _) => "suspend",
+            (Action::GoHome, _) => "Home tab",
+            (Action::GoTopics, _) => "Topics tab",
+            (Action::GoAdd, _) => "Add tab",
+            (Action::GoActivity, _) => "Activity tab",
+            (Action::Review, _) => "weekly review",
+            (Action::Profiles, _) => "switch profile",
+            (Action::Up, KeyContext::Activity) => "previous day",
+            (Action::Down, KeyContext::Activity) => "next day",
+            (Action::Left, KeyContext::Activity) => "previous week",
+            (Action::Right, KeyContext::Activity) => "next week",
+            (Action::Up, KeyContext::ReviewPopup) => "scroll up",
+            (Action::Down, KeyContext::ReviewPopup) => "scroll down",
+            (Action::Up, _) => "previous",
+            (Action::Down, _) => "next",
+            (Action::Left, _) => "back to Topics",
+            (Action::Right, _) => "open Items",
+            (Action::First, _) => "first",
+            (Action::Last, _) => "last",
+            (Action::Update, _) => "update progress",
+            (Action::Delete, KeyContext::Topics) => "delete Topic",
+            (Action::Delete, _) => "delete Item",
+            (Action::Increment, _) => "+1%",
+            (Action::Decrement, _) => "-1%",
+            (Action::FinishOnce, _) => "finish once",
+            (Action::Confirm | Action::Cancel, KeyContext::ErrorPopup) => "dismiss",
+            (Action::Confirm, KeyContext::Add) => "next box / add Item",
+            (Action::Confirm, KeyContext::UpdatePopup) => "save",
+            (Action::Confirm, KeyContext::ProfilePopup) => "switch",
+            (Action::Confirm, _) => "confirm",
+            (Action::Cancel, KeyContext::Add) => "previous box",
+            (Action::Cancel, KeyContext::Help) => "close",
+            (Action::Cancel, _) => "cancel",
+            (Action::Filter, _) => "change filter",
+            (Action::Range, _) => "week / month",
+            (Action::Help, KeyContext::Help) => "close",
+            (Action::Help, _) => "help",
+        }
+    }
+
+    /// Actions that can be triggered in `context`, in the order they are looked up.
+    pub fn in_context(context: KeyContext) -> Vec<Action> {
+        use Action::*;
+
+        let mut actions = vec![Quit, Suspend];
+        if context != KeyContext::ErrorPopup {
+            actions.push(Help);
+        }
+        if !context.is_popup() {
+            actions.extend([GoHome, GoTopics, GoAdd, GoActivity, Review, Profiles]);
+        }
+        actions.extend(match context {
+            KeyContext::Home => vec![],
+            KeyContext::Topics => vec![Up, Down, Right, First, Last, Delete],
+            KeyContext::Items => vec![Up, Down, Left, First, Last, Update, Delete],
+            KeyContext::Add => vec![Confirm, Cancel],
+            KeyContext::Activity => vec![Up, Down, Left, Right, Filter],
+            KeyContext::UpdatePopup => vec![Increment, Decrement, FinishOnce, Confirm, Cancel],
+            KeyContext::DeletePopup => vec![Confirm, Cancel],
+            KeyContext::ReviewPopup => vec![Up, Down, Range, Cancel],
+            KeyContext::ProfilePopup => vec![Up, Down, Confirm, Cancel],
+            KeyContext::Help => vec![Cancel],
+            KeyContext::ErrorPopup => vec![Confirm, Cancel],
+        });
+        actions
+    }
+}
+
+/// A single key press, ignoring Shift for characters since it is already in the character.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let modifiers = match event.code {
+            KeyCode::Char(_) => event.modifiers - KeyModifiers::SHIFT,
+            _ => event.modifiers,
+        };
+        Self {
+            code: event.code,
+            modifiers,
+        }
+    }
+}
+
+impl Key {
+    /// Whether typing this key in a text box should insert text rather than trigger an Action.
+    fn is_text(&self) -> bool {
+        matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty()
+    }
+}
+
+impl std::str::FromStr for Key {
+    type Err = String;
+
+    /// Parses keys like `alt+q`, `ctrl+shift+left`, `end`, `G` or `f5`.
+    fn from_str(input: &str) -> Result<Key, Self::Err> {
+        let err = || format!("invalid key '{input}'");
+        let mut parts: Vec<&str> = input.split('+').collect();
+        // A lone "+" or a binding ending in "++" means the plus key itself.
+        if input.ends_with("++") || input == "+" {
+            parts.truncate(parts.len().saturating_sub(2));
+            parts.push("+");
+        }
+        let name = parts
+            .pop()
+            .filter(|name| !name.is_empty())
+            .ok_or_else(err)?;
+
+        let mut modifiers = KeyModifiers::NONE;
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(err()),
+            };
+        }
+
+        let code = match name.to_lowercase().as_str() {
+            "enter" | "return" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            f if f.len() > 1 && f.starts_with('f') => {
+                KeyCode::F(f[1..].parse().map_err(|_| err())?)
+            }
+            _ => {
+                let mut chars = name.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+                        KeyCode::Char(c.to_ascii_uppercase())
+                    }
+                    (Some(c), None) => KeyCode::Char(c),
+                    _ => return Err(err()),
+                }
+            }
+        };
+
+        Ok(Key::from(KeyEvent::new(code, modifiers)))
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}")?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{c}"),
+            KeyCode::F(n) => write!(f, "F{n}"),
+            KeyCode::Left => write!(f, "<-"),
+            KeyCode::Right => write!(f, "->"),
+            code => write!(f, "{code:?}"),
+        }
+    }
+}
+
+/// One or more keys pressed one after the other, like `g g`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct KeySequence(Vec<Key>);
+
+impl std::str::FromStr for KeySequence {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<KeySequence, Self::Err> {
+        let keys = input
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<Result<Vec<Key>, String>>()?;
+        match keys.is_empty() {
+            true => Err(String::from("empty key binding")),
+            false => Ok(KeySequence(keys)),
+        }
+    }
+}
+
+impl fmt::Display for KeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keys: Vec<String> = self.0.iter().map(Key::to_string).collect();
+        write!(f, "{}", keys.join(" "))
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Preset {
+    #[default]
+    Default,
+    Vim,
+}
+
+/// The `[keys]` table of the config file.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeysConfig {
+    pub preset: Preset,
+    /// Replaces all keys of an Action, e.g. `quit = ["ctrl+q"]`.
+    pub bindings: HashMap<String, Vec<String>>,
+}
+
+/// What the keys pressed so far amount to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Resolved {
+    Action(Action),
+    /// The keys start a longer binding, wait for the next one.
+    Pending,
+    Unbound,
+}
+
+#[derive(Clone, Debug)]
+pub struct KeyMap {
+    bindings: HashMap<Action, Vec<KeySequence>>,
+}
+
+fn default_bindings(preset: Preset) -> HashMap<Action, Vec<&'static str>> {
+    use Action::*;
+
+    let mut bindings = HashMap::from([
+        (Quit, vec!["alt+q", "end", "ctrl+c"]),
+        (Suspend, vec!["ctrl+z"]),
+        (GoHome, vec!["alt+h"]),
+        (GoTopics, vec!["alt+t", "home"]),
+        (GoAdd, vec!["alt+a", "insert"]),
+        (GoActivity, vec!["alt+v"]),
+        (Review, vec!["alt+r"]),
+        (Profiles, vec!["alt+p"]),
+        (Up, vec!["up"]),
+        (Down, vec!["down"]),
+        (Left, vec!["left"]),
+        (Right, vec!["right"]),
+        (First, vec![]),
+        (Last, vec![]),
+        (Update, vec!["enter"]),
+        (Delete, vec!["delete"]),
+        (Increment, vec!["right"]),
+        (Decrement, vec!["left"]),
+        (FinishOnce, vec!["tab"]),
+        (Confirm, vec!["enter"]),
+        (Cancel, vec!["esc"]),
+        (Filter, vec!["f"]),
+        (Range, vec!["tab"]),
+        (Help, vec!["?", "f1"]),
+    ]);
+    if preset == Preset::Vim {
+        for (action, keys) in [
+            (Up, vec!["k"]),
+            (Down, vec!["j"]),
+            (Left, vec!["h"]),
+            (Right, vec!["l"]),
+            (Increment, vec!["l"]),
+            (Decrement, vec!["h"]),
+            (First, vec!["g g"]),
+            (Last, vec!["G"]),
+            (Delete, vec!["d d"]),
+        ] {
+            bindings.entry(action).or_default().extend(keys);
+        }
+    }
+    bindings
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        KeyMap::from_config(&KeysConfig::default()).expect("default key bindings are valid")
+    }
+}
+
+impl KeyMap {
+    /// Builds the key map of a preset with the config overrides applied.
+    ///
+    /// Errors name the config key at fault, e.g. `keys.bindings.quit`.
+    pub fn from_config(config: &KeysConfig) -> Result<Self, (String, String)> {
+        let mut bindings = HashMap::new();
+        for (action, keys) in default_bindings(config.preset) {
+            let keys = keys.iter().map(|k| k.parse().expect("valid default key"));
+            bindings.insert(action, keys.collect());
+        }
+        for (name, keys) in config.bindings.iter() {
+            let key_path = format!("keys.bindings.{name}");
+            let action = Action::ALL
+                .into_iter()
+                .find(|action| action.config_name() == name)
+                .ok_or_else(|| (key_path.clone(), String::from("is not a known action")))?;
+            let keys = keys
+                .iter()
+                .map(|k| k.parse())
+                .collect::<Result<Vec<KeySequence>, String>>()
+                .map_err(|err| (key_path, err))?;
+            bindings.insert(action, keys);
+        }
+
+        let keymap = KeyMap { bindings };
+        keymap.check_conflicts()?;
+        Ok(keymap)
+    }
+
+    /// Makes sure no two Actions of the same context share a key, or the start of one.
+    fn check_conflicts(&self) -> Result<(), (String, String)> {
+        for context in KeyContext::ALL {
+            let actions = Action::in_context(context);
+            for (i, a) in actions.iter().enumerate() {
+                for b in actions.iter().skip(i + 1) {
+                    for seq_a in self.keys(*a) {
+                        for seq_b in self.keys(*b) {
+                            let shorter = seq_a.0.len().min(seq_b.0.len());
+                            if seq_a.0[..shorter] == seq_b.0[..shorter] {
+                                return Err((
+                                    format!("keys.bindings.{}", b.config_name()),
+                                    format!(
+                                        "'{}' clashes with '{}' of {} in {:?}",
+                                        seq_b,
+                                        seq_a,
+                                        a.config_name(),
+                                        context
+                                    ),
+                                ));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn keys(&self, action: Action) -> &[KeySequence] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Keys of `action` that work in `context`, leaving out letters typed into the Add form.
+    fn keys_in(&self, action: Action, context: KeyContext) -> Vec<&KeySequence> {
+        self.keys(action)
+            .iter()
+            .filter(|sequence| context != KeyContext::Add || !sequence.0.iter().any(Key::is_text))
+            .collect()
+    }
+
+    /// The keys of `action` in `context` for running text, like `Alt+t or Home`.
+    pub fn describe(&self, action: Action, context: KeyContext) -> String {
+        let keys: Vec<String> = self
+            .keys_in(action, context)
+            .iter()
+            .map(|k| k.to_string())
+            .collect();
+        match keys.split_last() {
+            None => String::from("(unbound)"),
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {last}", rest.join(", ")),
+        }
+    }
+
+    /// Every bound Action of `context` with its keys, for the help overlay.
+    pub fn help(&self, context: KeyContext) -> Vec<(String, &'static str)> {
+        Action::in_context(context)
+            .into_iter()
+            .filter_map(|action| {
+                let keys = self.keys_in(action, context);
+                if keys.is_empty() {
+                    return None;
+                }
+                let keys: Vec<String> = keys.iter().map(|k| k.to_string()).collect();
+                Some((keys.join(", "), action.description(context)))
+            })
+            .collect()
+    }
+
+    /// One line of hints for the footer: the first key of each Action of `context` that is
+    /// not reachable from the Menu anyway.
+    pub fn hints(&self, context: KeyContext) -> String {
+        Action::in_context(context)
+            .into_iter()
+            .filter(|action| {
+                !matches!(
+                    action,
+                    Action::GoHome
+                        | Action::GoTopics
+                        | Action::GoAdd
+                        | Action::GoActivity
+                        | Action::Suspend
+                )
+            })
+            .filter_map(|action| {
+                let key = *self.keys_in(action, context).first()?;
+                Some(format!("{key}: {}", action.description(context)))
+            })
+            .collect::<Vec<String>>()
+            .join("  ")
+    }
+
+    /// Resolves the keys pressed so far in `context`.
+    ///
+    /// `pressed` is cleared unless it starts a longer binding. If the keys lead nowhere the
+    /// last key is tried on its own, so a stray key does not swallow the one after it.
+    pub fn resolve(&self, pressed: &mut Vec<KeyEvent>, context: KeyContext) -> Resolved {
+        let keys: Vec<Key> = pressed.iter().map(|&k| Key::from(k)).collect();
+        let mut resolved = self.resolve_keys(&keys, context);
+        if resolved == Resolved::Unbound && keys.len() > 1 {
+            pressed.drain(..keys.len() - 1);
+            resolved = self.resolve_keys(&keys[keys.len() - 1..], context);
+        }
+        if resolved != Resolved::Pending {
+            pressed.clear();
+        }
+        resolved
+    }
+
+    fn resolve_keys(&self, pressed: &[Key], context: KeyContext) -> Resolved {
+        // Letters typed into the Add form are text, not shortcuts.
+        if context == KeyContext::Add && pressed.iter().any(Key::is_text) {
+            return Resolved::Unbound;
+        }
+        let mut pending = false;
+        for action in Action::in_context(context) {
+            for sequence in self.keys(action) {
+                if sequence.0 == pressed {
+                    return Resolved::Action(action);
+                }
+                if sequence.0.starts_with(pressed) {
+                    pending = true;
+                }
+            }
+        }
+        match pending {
+            true => Resolved::Pending,
+            false => Resolved::Unbound,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Deserialize)]
+    struct Config {
+        keys: KeysConfig,
+    }
+
+    /// The `[keys]` example of the README.
+    fn readme_example() -> &'static str {
+        let readme = include_str!("../README.md");
+        let start = readme
+            .find("```toml\n[keys]")
+            .expect("README has a [keys] example");
+        let block = &readme[start + "```toml\n".len()..];
+        &block[..block.find("```").unwrap()]
+    }
+
+    #[test]
+    fn readme_example_loads() {
+        let config: Config = toml::from_str(readme_example()).unwrap();
+        let keymap = KeyMap::from_config(&config.keys).unwrap();
+        let first: Vec<KeySequence> = ["g g", "ctrl+home"].map(|k| k.parse().unwrap()).into();
+        assert_eq!(keymap.keys(Action::First), first);
+        assert_eq!(keymap.keys(Action::Quit).len(), 2);
+    }
+
+    #[test]
+    fn clashing_bindings_are_rejected() {
+        let config = KeysConfig {
+            preset: Preset::Vim,
+            bindings: HashMap::from([(String::from("first"), vec![String::from("home")])]),
+        };
+        let (key, _) = KeyMap::from_config(&config).unwrap_err();
+        assert!(key == "keys.bindings.first" || key == "keys.bindings.go_topics");
+    }
+
+    #[test]
+    fn describes_the_bound_keys() {
+        let keymap = KeyMap::default();
+        assert_eq!(
+            keymap.describe(Action::GoTopics, KeyContext::Home),
+            "Alt+t or Home"
+        );
+        assert_eq!(
+            keymap.describe(Action::Quit, KeyContext::Home),
+            "Alt+q, End or Ctrl+c"
+        );
+
+        let config = KeysConfig {
+            preset: Preset::Default,
+            bindings: HashMap::from([
+                (String::from("go_topics"), vec![String::from("alt+e")]),
+                (
+                    String::from("confirm"),
+                    vec![String::from("y"), String::from("f2")],
+                ),
+                (String::from("first"), vec![]),
+            ]),
+        };
+        let keymap = KeyMap::from_config(&config).unwrap();
+        assert_eq!(keymap.describe(Action::GoTopics, KeyContext::Add), "Alt+e");
+        // Letters are typed into the Add form rather than confirming it.
+        assert_eq!(
+            keymap.describe(Action::Confirm, KeyContext::DeletePopup),
+            "y or F2"
+        );
+        assert_eq!(keymap.describe(Action::Confirm, KeyContext::Add), "F2");
+        assert_eq!(
+            keymap.describe(Action::First, KeyContext::Topics),
+            "(unbound)"
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! The data model behind the finish-it TUI, for tools that want to read or change the same
+//! database.
+//!
+//! A [`Repository`] opens a database and hands out [`Topic`]s and [`Item`]s through the
+//! [`Store`] trait. Progress is changed on the Item with the same rules the TUI uses, then
+//! saved with [`Store::update_item`], which logs the change in the [`History`]:
+//!
+//! ```
+//! use finish_it::{Item, Repository, Store};
+//!
+//! let repo = Repository::open_in_memory()?;
+//! repo.add_item(&Item {
+//!     name: String::from("Read SICP"),
+//!     topicname: String::from("Books"),
+//!     percentage: 99,
+//!     ..Item::default()
+//! })?;
+//!
+//! let mut item = repo.items("Books")?.remove(0);
+//! item.increment_one();
+//! repo.update_item(&item)?;
+//!
+//! assert!(repo.items("Books")?[0].is_done());
+//! # Ok::<(), finish_it::StoreError>(())
+//! ```
+//!
+//! A [`MemoryStore`] works the same without a database, for tests and throwaway sessions.
+//!
+//! Everything exported here is a stable API. The finish-it binary is built on it as well.
+
+#![warn(missing_docs)]
+
+pub mod backup;
+mod db;
+pub mod doctor;
+pub mod error;
+pub mod formats;
+pub mod memory;
+pub mod model;
+pub mod report;
+pub mod repository;
+pub mod store;
+
+pub use error::StoreError;
+pub use memory::MemoryStore;
+pub use model::{Forecast, History, Item, Topic};
+pub use repository::{describe_error, Repository};
+pub use store::Store;
@@ -0,0 +1,346 @@
+//! A Store that keeps everything in memory, for tests and sessions that should leave no trace.
+
+use std::cell::RefCell;
+
+use chrono::{DateTime, Utc};
+
+use crate::error::StoreError;
+use crate::model::{History, Item, Topic};
+use crate::store::{check_item, Store};
+
+/// Topics, Items and History that live only as long as the MemoryStore does.
+///
+/// Ids, ordering and History entries follow the SQLite [`Repository`](crate::Repository), so
+/// code tested against one works against the other.
+#[derive(Debug, Default)]
+pub struct MemoryStore {
+    data: RefCell<Data>,
+}
+
+#[derive(Debug, Default)]
+struct Data {
+    topics: Vec<Topic>,
+    items: Vec<Item>,
+    history: Vec<History>,
+    /// Like SQLite's AUTOINCREMENT, ids of deleted Items are never handed out again.
+    last_item_id: usize,
+}
+
+impl Data {
+    fn add_topic(&mut self, name: &str) {
+        if !self.topics.iter().any(|topic| topic.name == name) {
+            self.topics.push(Topic {
+                name: name.to_owned(),
+                created: Utc::now(),
+            });
+        }
+    }
+
+    fn log(&mut self, item: &Item, action: &str, delta: i64) {
+        self.history.push(History {
+            id: self.history.len() + 1,
+            itemid: item.id,
+            itemname: item.name.to_owned(),
+            topicname: item.topicname.to_owned(),
+            action: action.to_owned(),
+            percentage: item.percentage,
+            timesfinished: item.timesfinished,
+            delta,
+            created: Utc::now(),
+        });
+    }
+
+    fn import_item(&mut self, item: &Item) {
+        self.add_topic(&item.topicname);
+        let unrelated = self
+            .items
+            .iter()
+            .any(|old| old.id == item.id && !old.is_same_item(item));
+        let id = match item.id {
+            0 => self.last_item_id + 1,
+            _ if unrelated => self.last_item_id + 1,
+            id => id,
+        };
+        self.last_item_id = self.last_item_id.max(id);
+        let item = Item { id, ..item.clone() };
+        match self.items.iter_mut().find(|old| old.id == id) {
+            Some(old) => *old = item.clone(),
+            None => {
+                self.items.push(item.clone());
+                self.items.sort_by_key(|item| item.id);
+            }
+        }
+        self.log(&item, "import", 0);
+    }
+}
+
+impl MemoryStore {
+    /// An empty MemoryStore.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl Store for MemoryStore {
+    fn topics(&self) -> Result<Vec<Topic>, StoreError> {
+        Ok(self.data.borrow().topics.clone())
+    }
+
+    fn items(&self, topic: &str) -> Result<Vec<Item>, StoreError> {
+        Ok(self
+            .data
+            .borrow()
+            .items
+            .iter()
+            .filter(|item| item.topicname == topic)
+            .cloned()
+            .collect())
+    }
+
+    fn all_items(&self) -> Result<Vec<Item>, StoreError> {
+        let mut items = self.data.borrow().items.clone();
+        items.sort_by(|a, b| (&a.topicname, a.id).cmp(&(&b.topicname, b.id)));
+        Ok(items)
+    }
+
+    fn history(&self, since: DateTime<Utc>) -> Result<Vec<History>, StoreError> {
+        Ok(self
+            .data
+            .borrow()
+            .history
+            .iter()
+            .filter(|entry| entry.created >= since)
+            .cloned()
+            .collect())
+    }
+
+    fn topic_history(&self, topic: &str) -> Result<Vec<History>, StoreError> {
+        let data = self.data.borrow();
+        Ok(data
+            .history
+            .iter()
+            .filter(|entry| {
+                data.items
+                    .iter()
+                    .any(|item| item.id == entry.itemid && item.topicname == topic)
+            })
+            .cloned()
+            .collect())
+    }
+
+    fn add_item(&self, item: &Item) -> Result<(), StoreError> {
+        check_item(item)?;
+        let mut data = self.data.borrow_mut();
+        data.add_topic(&item.topicname);
+        data.last_item_id += 1;
+        let item = Item {
+            id: data.last_item_id,
+            created: Utc::now(),
+            ..item.clone()
+        };
+        data.items.push(item.clone());
+        data.log(&item, "insert", 0);
+        Ok(())
+    }
+
+    fn import_items(&self, items: &[Item]) -> Result<usize, StoreError> {
+        items.iter().try_for_each(check_item)?;
+        let mut data = self.data.borrow_mut();
+        for item in items {
+            data.import_item(item);
+        }
+        Ok(items.len())
+    }
+
+    fn update_item(&self, item: &Item) -> Result<(), StoreError> {
+        check_item(item)?;
+        let mut data = self.data.borrow_mut();
+        let stored = data
+            .items
+            .iter_mut()
+            .find(|stored| stored.id == item.id)
+            .ok_or(StoreError::NotFound(item.id))?;
+        let old = Item {
+            percentage: stored.percentage,
+            timesfinished: stored.timesfinished,
+            ..item.clone()
+        };
+        stored.percentage = item.percentage;
+        stored.timesfinished = item.timesfinished;
+        let delta = item.progress_points() - old.progress_points();
+        data.log(item, "update", delta);
+        Ok(())
+    }
+
+    fn delete_item(&self, item: &Item) -> Result<(), StoreError> {
+        let mut data = self.data.borrow_mut();
+        if let Some(index) = data.items.iter().position(|stored| stored.id == item.id) {
+            let stored = data.items.remove(index);
+            data.log(&stored, "delete", 0);
+        }
+        Ok(())
+    }
+
+    fn delete_topic(&self, topic: &Topic) -> Result<(), StoreError> {
+        let mut data = self.data.borrow_mut();
+        let (deleted, kept): (Vec<Item>, Vec<Item>) = std::mem::take(&mut data.items)
+            .into_iter()
+            .partition(|item| item.topicname == topic.name);
+        data.items = kept;
+        for item in deleted.iter() {
+            data.log(item, "delete", 0);
+        }
+        data.topics.retain(|stored| stored.name != topic.name);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::Repository;
+
+    /// Topic names, Items and `(itemid, action, delta)` of every History entry.
+    type Contents = (Vec<String>, Vec<Item>, Vec<(usize, String, i64)>);
+
+    /// Makes the same changes through `store` as the TUI would and returns what it ends up with,
+    /// without the timestamps the two Stores can't agree on.
+    fn exercise(store: &dyn Store) -> Contents {
+        let imported = Item {
+            id: 7,
+            name: String::from("Water plants"),
+            topicname: String::from("Garden"),
+            isrecurring: 1,
+            percentage: 99,
+            ..Item::default()
+        };
+        store.import_items(&[imported]).unwrap();
+        for name in ["Read SICP", "Read TAOCP"] {
+            let item = Item {
+                name: name.to_string(),
+                topicname: String::from("Books"),
+                percentage: 40,
+                ..Item::default()
+            };
+            store.add_item(&item).unwrap();
+        }
+
+        let mut plants = store.items("Garden").unwrap().remove(0);
+        plants.increment_one();
+        plants.increment_one();
+        store.update_item(&plants).unwrap();
+        let books = store.items("Books").unwrap();
+        store.delete_item(&books[1]).unwrap();
+        store.add_item(&books[1]).unwrap();
+        let garden = store.topics().unwrap().remove(0);
+        store.delete_topic(&garden).unwrap();
+
+        let topics = store
+            .topics()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        let items = store
+            .all_items()
+            .unwrap()
+            .into_iter()
+            .map(|item| Item {
+                created: DateTime::default(),
+                ..item
+            })
+            .collect();
+        let history = store
+            .history(Utc::now() - Duration::days(1))
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.itemid, entry.action, entry.delta))
+            .collect();
+        (topics, items, history)
+    }
+
+    #[test]
+    fn behaves_like_sqlite() {
+        let memory = exercise(&MemoryStore::new());
+        assert_eq!(memory, exercise(&Repository::open_in_memory().unwrap()));
+        assert_eq!(memory.0, vec!["Books"]);
+        assert_eq!(
+            memory.1.iter().map(|item| item.id).collect::<Vec<usize>>(),
+            vec![8, 10]
+        );
+        assert_eq!(memory.2[3], (7, String::from("update"), 3));
+    }
+
+    /// Whether each change `store` is asked for is refused, and if it is, what is left.
+    fn refusals(store: &dyn Store) -> (Vec<String>, usize, Vec<Item>) {
+        let valid = Item {
+            name: String::from("Read SICP"),
+            topicname: String::from("Books"),
+            percentage: 40,
+            ..Item::default()
+        };
+        store.add_item(&valid).unwrap();
+        let stored = store.items("Books").unwrap().remove(0);
+
+        let invalid = [
+            Item {
+                percentage: 101,
+                ..valid.clone()
+            },
+            Item {
+                isrecurring: 2,
+                ..valid.clone()
+            },
+            Item {
+                timesfinished: usize::MAX,
+                ..valid.clone()
+            },
+        ];
+        let mut errors = Vec::new();
+        for item in invalid.iter() {
+            errors.push(store.add_item(item).unwrap_err().to_string());
+            let update = Item {
+                id: stored.id,
+                ..item.clone()
+            };
+            errors.push(store.update_item(&update).unwrap_err().to_string());
+        }
+        // One invalid Item keeps the valid ones before it from being imported.
+        let imported = [valid.clone(), invalid[0].clone()];
+        errors.push(store.import_items(&imported).unwrap_err().to_string());
+        let missing = Item {
+            id: 99,
+            ..valid.clone()
+        };
+        let not_found = store.update_item(&missing).unwrap_err();
+        assert!(matches!(not_found, StoreError::NotFound(99)));
+
+        let items = store
+            .all_items()
+            .unwrap()
+            .into_iter()
+            .map(|item| Item {
+                created: DateTime::default(),
+                ..item
+            })
+            .collect();
+        let history = store.history(Utc::now() - Duration::days(1)).unwrap();
+        (errors, history.len(), items)
+    }
+
+    #[test]
+    fn refuses_what_sqlite_refuses() {
+        let memory = refusals(&MemoryStore::new());
+        assert_eq!(memory, refusals(&Repository::open_in_memory().unwrap()));
+        assert_eq!(memory.0.len(), 7);
+        assert_eq!(
+            memory.0[0],
+            "The change was refused: Item \"Read SICP\": percentage is 101, expected 0 to 100"
+        );
+        assert_eq!(memory.1, 1);
+        assert_eq!(memory.2.len(), 1);
+        assert_eq!(memory.2[0].percentage, 40);
+    }
+}
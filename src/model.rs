@@ -0,0 +1,344 @@
+//! Topics, the Items tracked in them and the History of changes made to Items.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A group of Items, identified by its name.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Topic {
+    /// Unique name, also used by Items to refer to their Topic.
+    pub name: String,
+    /// When the Topic was added.
+    pub created: DateTime<Utc>,
+}
+
+/// Something to finish, either once or over and over again.
+///
+/// Progress is kept as a percentage of the current round plus the number of rounds finished.
+/// Change it with `increment_one`, `decrement_one` and `finish_once`, which follow the same
+/// rules as the TUI.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Item {
+    /// Assigned by the database, 0 for Items that have not been stored yet.
+    pub id: usize,
+    /// Name shown in the Items table, need not be unique.
+    pub name: String,
+    /// Name of the Topic the Item belongs to.
+    pub topicname: String,
+    /// 1 if the Item starts over once finished, 0 if it is done for good.
+    pub isrecurring: u8,
+    /// Progress of the current round, from 0 to 100.
+    pub percentage: u8,
+    /// Rounds finished so far, at most 1 for a one time Item.
+    pub timesfinished: usize,
+    /// Days after `created` the Item should be finished in, 0 for no limit.
+    pub daylimit: usize,
+    /// When the Item was added.
+    pub created: DateTime<Utc>,
+}
+
+/// One change made to an Item: added, updated, imported or deleted.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct History {
+    /// Assigned by the database, in the order the changes were made.
+    pub id: usize,
+    /// Id of the changed Item.
+    pub itemid: usize,
+    /// Name of the Item at the time, so the entry outlives the Item.
+    pub itemname: String,
+    /// Name of the Item's Topic at the time.
+    pub topicname: String,
+    /// `insert`, `update`, `import`, `delete`, or `repair` for a change made by `doctor --fix`.
+    pub action: String,
+    /// Progress of the Item after the change.
+    pub percentage: u8,
+    /// Rounds finished after the change.
+    pub timesfinished: usize,
+    /// Progress gained by the change in percentage points, see `Item::progress_points`.
+    pub delta: i64,
+    /// When the change was made.
+    pub created: DateTime<Utc>,
+}
+
+/// How many days back [`Item::forecast`] looks for the pace an Item is making progress at.
+pub const FORECAST_DAYS: i64 = 30;
+
+/// When an Item is expected to be finished at the pace it has been making progress.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct Forecast {
+    /// Observed progress in percentage points per day.
+    pub rate: f64,
+    /// When the Item should reach 100%, if it is making progress and not done yet.
+    pub eta: Option<DateTime<Utc>>,
+    /// Whether `eta` falls after the Item's day limit.
+    pub late: bool,
+}
+
+impl Item {
+    /// A progress bar `width` characters wide.
+    pub fn get_dot_vec(&self, width: usize) -> String {
+        let p = (self.percentage.min(100) as usize * width) / 100;
+        let r = width - p;
+
+        let filled = "█".repeat(p);
+        let remaining = "░".repeat(r);
+        [filled, remaining].concat()
+    }
+
+    /// Total progress in percentage points, counting every finished round of a recurring Item as 100.
+    pub fn progress_points(&self) -> i64 {
+        match self.isrecurring {
+            1 => self.timesfinished as i64 * 100 + self.percentage as i64,
+            _ => self.percentage as i64,
+        }
+    }
+
+    /// Adds one percent. Reaching 100 finishes the Item, or starts the next round of a
+    /// recurring one.
+    pub fn increment_one(&mut self) {
+        match self.isrecurring {
+            0 => {
+                if self.percentage + 1 < 100 {
+                    self.percentage += 1;
+                } else {
+                    self.percentage = 100;
+                    self.timesfinished = 1;
+                }
+            }
+            1 => {
+                if self.percentage + 1 < 100 {
+                    self.percentage += 1;
+                } else {
+                    self.percentage = 1;
+                    self.timesfinished += 1;
+                }
+            }
+            _ => {}
+        };
+    }
+
+    /// Takes one percent off, going back into the previous round of a recurring Item.
+    pub fn decrement_one(&mut self) {
+        match self.isrecurring {
+            0 => {
+                self.percentage = self.percentage.saturating_sub(1);
+                if self.percentage < 100 {
+                    self.timesfinished = 0;
+                }
+            }
+            1 => match self.timesfinished {
+                0 => self.percentage = self.percentage.saturating_sub(1),
+                _ => match self.percentage.checked_sub(1) {
+                    Some(n) => self.percentage = n,
+                    None => {
+                        self.percentage = 100;
+                        self.timesfinished -= 1;
+                    }
+                },
+            },
+            _ => {}
+        };
+    }
+
+    /// Finishes the Item, or the current round of a recurring one.
+    pub fn finish_once(&mut self) {
+        match self.isrecurring {
+            0 => {
+                self.percentage = 100;
+                self.timesfinished = 1;
+            }
+            1 => {
+                self.percentage = 0;
+                self.timesfinished += 1;
+            }
+            _ => {}
+        };
+    }
+
+    /// Days until the day limit runs out, negative once it has, or `N/A` without a limit.
+    pub fn days_left(&self) -> String {
+        if self.daylimit == 0 {
+            return String::from("N/A");
+        }
+        let days_passed = DateTime::signed_duration_since(chrono::offset::Utc::now(), self.created);
+        let days_remaining = self.daylimit as i64 - days_passed.num_days();
+        days_remaining.to_string()
+    }
+
+    /// When the day limit runs out, if there is one.
+    pub fn deadline(&self) -> Option<DateTime<Utc>> {
+        match self.daylimit {
+            0 => None,
+            n => Some(self.created + chrono::Duration::days(n as i64)),
+        }
+    }
+
+    /// Whether `imported` is this Item exported and read back, maybe renamed or moved in the
+    /// other tool: the id is the same and so are either the name and Topic or the creation time.
+    /// Creation times are compared to the second, the precision most formats keep.
+    pub fn is_same_item(&self, imported: &Item) -> bool {
+        self.id == imported.id
+            && ((self.name == imported.name && self.topicname == imported.topicname)
+                || self.created.timestamp() == imported.created.timestamp())
+    }
+
+    /// Whether a one time Item is finished. Recurring Items never are.
+    pub fn is_done(&self) -> bool {
+        self.isrecurring == 0 && self.percentage >= 100
+    }
+
+    /// Projects when the Item reaches 100% again, at the pace of the updates in `history` over
+    /// the last [`FORECAST_DAYS`] days, or since the Item was added if it is younger.
+    ///
+    /// `history` may hold changes to other Items, only this Item's updates count. Progress the
+    /// Item started or was imported with is not a pace, so Items without recent updates have no
+    /// ETA.
+    pub fn forecast(&self, history: &[History]) -> Forecast {
+        self.forecast_at(history, Utc::now())
+    }
+
+    fn forecast_at(&self, history: &[History], now: DateTime<Utc>) -> Forecast {
+        let window_start = now - chrono::Duration::days(FORECAST_DAYS);
+        let progress: i64 = history
+            .iter()
+            .filter(|entry| entry.itemid == self.id && entry.action == "update")
+            .filter(|entry| entry.created > window_start && entry.created <= now)
+            .map(|entry| entry.delta)
+            .sum();
+        let days_tracked = (now - self.created.max(window_start)).num_seconds() as f64 / 86400.0;
+        let rate = progress.max(0) as f64 / days_tracked.max(1.0);
+
+        let eta = match (self.is_done(), rate > 0.0) {
+            (false, true) => {
+                let remaining = 100 - self.percentage.min(100) as i64;
+                let seconds = remaining as f64 / rate * 86400.0;
+                Some(now + chrono::Duration::seconds(seconds.min(1e11) as i64))
+            }
+            _ => None,
+        };
+        let late = match (eta, self.deadline()) {
+            (Some(eta), Some(deadline)) => eta > deadline,
+            _ => false,
+        };
+
+        Forecast { rate, eta, late }
+    }
+
+    /// The ETA of `forecast` as shown in the Items table.
+    pub fn eta_str(&self, forecast: &Forecast) -> String {
+        match (self.is_done(), forecast.eta) {
+            (true, _) => String::from("Done"),
+            (false, Some(eta)) if forecast.late => format!("{} (late)", eta.date_naive()),
+            (false, Some(eta)) => eta.date_naive().to_string(),
+            (false, None) => String::from("N/A"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    fn item(percentage: u8, days_old: i64, daylimit: usize, now: DateTime<Utc>) -> Item {
+        Item {
+            percentage,
+            daylimit,
+            created: now - Duration::days(days_old),
+            ..Item::default()
+        }
+    }
+
+    /// An update of the Item with id 0, `days_ago` days before `now`.
+    fn update(delta: i64, days_ago: i64, now: DateTime<Utc>) -> History {
+        History {
+            action: String::from("update"),
+            delta,
+            created: now - Duration::days(days_ago),
+            ..History::default()
+        }
+    }
+
+    #[test]
+    fn forecasts_from_the_updates_in_the_window() {
+        let now = Utc::now();
+        let history: Vec<History> = [18, 14, 10, 6]
+            .into_iter()
+            .map(|days_ago| update(10, days_ago, now))
+            .collect();
+
+        // 40% in 20 days, the other 60% take 30 more.
+        let steady = item(40, 20, 100, now).forecast_at(&history, now);
+        assert_eq!(steady.rate, 2.0);
+        assert_eq!(steady.eta, Some(now + Duration::days(30)));
+        assert!(!steady.late);
+
+        let behind = item(40, 20, 40, now).forecast_at(&history, now);
+        assert_eq!(behind.eta, Some(now + Duration::days(30)));
+        assert!(behind.late);
+
+        // Items younger than a day are not projected to finish within hours.
+        let new = item(10, 0, 0, now).forecast_at(&[update(10, 0, now)], now);
+        assert_eq!(new.rate, 10.0);
+        assert_eq!(new.eta, Some(now + Duration::days(9)));
+
+        let recurring = Item {
+            isrecurring: 1,
+            timesfinished: 3,
+            ..item(50, 35, 0, now)
+        };
+        let rounds = [update(100, 20, now), update(50, 5, now)];
+        assert_eq!(recurring.forecast_at(&rounds, now).rate, 5.0);
+        assert_eq!(
+            recurring.forecast_at(&rounds, now).eta,
+            Some(now + Duration::days(10))
+        );
+    }
+
+    #[test]
+    fn forecasts_follow_the_recent_pace_of_bursts() {
+        let now = Utc::now();
+        let bursty = item(85, 90, 0, now);
+        let mut history = vec![
+            // The big burst two months ago is outside the window...
+            update(70, 60, now),
+            // ...only the last 15 points in 30 days set the pace.
+            update(10, 3, now),
+            update(5, 1, now),
+            // Changes that are not progress, or are another Item's, do not count.
+            History {
+                action: String::from("repair"),
+                ..update(40, 2, now)
+            },
+            History {
+                itemid: 7,
+                ..update(40, 2, now)
+            },
+        ];
+        let forecast = bursty.forecast_at(&history, now);
+        assert_eq!(forecast.rate, 0.5);
+        assert_eq!(forecast.eta, Some(now + Duration::days(30)));
+
+        // Without updates in the window the old burst says nothing about when it ends.
+        history.retain(|entry| entry.delta == 70);
+        let stalled = bursty.forecast_at(&history, now);
+        assert_eq!((stalled.rate, stalled.eta), (0.0, None));
+    }
+
+    #[test]
+    fn items_without_progress_or_done_have_no_eta() {
+        let now = Utc::now();
+        let idle = item(0, 20, 10, now).forecast_at(&[], now);
+        assert_eq!((idle.rate, idle.eta, idle.late), (0.0, None, false));
+        assert_eq!(item(0, 20, 10, now).eta_str(&idle), "N/A");
+
+        let done = Item {
+            timesfinished: 1,
+            ..item(100, 20, 10, now)
+        };
+        let forecast = done.forecast_at(&[update(60, 1, now)], now);
+        assert_eq!((forecast.eta, forecast.late), (None, false));
+        assert_eq!(done.eta_str(&forecast), "Done");
+    }
+}
@@ -0,0 +1,198 @@
+use std::time::{Duration, Instant};
+
+use tui::layout::Rect;
+
+const DOUBLE_CLICK: Duration = Duration::from_millis(400);
+
+/// Something on the screen a click can land on.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Target {
+    /// Index into the Menu titles, Quit being the last one.
+    Tab(usize),
+    Topic(usize),
+    Item(usize),
+    TextArea(usize),
+}
+
+/// Where the clickable widgets were drawn last, kept up to date while drawing.
+#[derive(Clone, Debug, Default)]
+pub struct Areas {
+    pub tabs: Rect,
+    pub tab_titles: Vec<usize>,
+    pub topics: Rect,
+    pub topic_offset: usize,
+    pub items: Rect,
+    pub item_offset: usize,
+    pub text_areas: Vec<Rect>,
+    last_click: Option<(Instant, Target)>,
+}
+
+fn contains(area: Rect, column: u16, row: u16) -> bool {
+    area.x <= column && column < area.right() && area.y <= row && row < area.bottom()
+}
+
+/// First row shown by a bordered List or Table after drawing, following how tui scrolls them
+/// since it does not hand out the offset. Every row is one line high.
+pub fn scroll_offset(offset: usize, selected: Option<usize>, len: usize, height: u16) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    let height = (height as usize).max(1);
+    let offset = offset.min(len - 1);
+    let selected = selected.unwrap_or(0).min(len - 1);
+    if selected >= offset + height {
+        selected + 1 - height
+    } else if selected < offset {
+        selected
+    } else {
+        offset
+    }
+}
+
+impl Areas {
+    /// Finds what is under the mouse.
+    pub fn target(&self, column: u16, row: u16) -> Option<Target> {
+        if contains(self.tabs, column, row) {
+            // Titles are padded by a space on each side and separated by a one column divider.
+            let mut x = self.tabs.x + 1;
+            for (i, width) in self.tab_titles.iter().enumerate() {
+                if x <= column && column < x + *width as u16 + 2 {
+                    return Some(Target::Tab(i));
+                }
+                x += *width as u16 + 3;
+            }
+            return None;
+        }
+        if contains(self.topics, column, row)
+            && row > self.topics.y
+            && row + 1 < self.topics.bottom()
+        {
+            let index = (row - self.topics.y - 1) as usize + self.topic_offset;
+            return Some(Target::Topic(index));
+        }
+        // The Items table has a header line under its top border.
+        if contains(self.items, column, row)
+            && row > self.items.y + 1
+            && row + 1 < self.items.bottom()
+        {
+            let index = (row - self.items.y - 2) as usize + self.item_offset;
+            return Some(Target::Item(index));
+        }
+        self.text_areas
+            .iter()
+            .position(|area| contains(*area, column, row))
+            .map(Target::TextArea)
+    }
+
+    /// Remembers a click on `target` and tells whether it completes a double click.
+    pub fn click(&mut self, target: Target) -> bool {
+        let now = Instant::now();
+        let double = self
+            .last_click
+            .is_some_and(|(at, last)| last == target && now - at < DOUBLE_CLICK);
+        self.last_click = match double {
+            true => None,
+            false => Some((now, target)),
+        };
+        double
+    }
+
+    /// Forgets everything drawn, before a frame only draws some of the widgets.
+    pub fn clear(&mut self) {
+        self.topics = Rect::default();
+        self.items = Rect::default();
+        self.text_areas.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The Topics tab of a 100x30 terminal: Menu on top, Topics left of the Items table.
+    fn areas() -> Areas {
+        Areas {
+            tabs: Rect::new(0, 0, 100, 3),
+            // " Home | Topics | Add "
+            tab_titles: vec![4, 6, 3],
+            topics: Rect::new(0, 3, 20, 10),
+            topic_offset: 0,
+            items: Rect::new(20, 3, 80, 10),
+            item_offset: 0,
+            ..Areas::default()
+        }
+    }
+
+    #[test]
+    fn finds_tabs_by_their_titles() {
+        let areas = areas();
+        assert_eq!(areas.target(0, 1), None);
+        assert_eq!(areas.target(1, 1), Some(Target::Tab(0)));
+        assert_eq!(areas.target(6, 1), Some(Target::Tab(0)));
+        assert_eq!(areas.target(7, 1), None);
+        assert_eq!(areas.target(8, 1), Some(Target::Tab(1)));
+        assert_eq!(areas.target(15, 1), Some(Target::Tab(1)));
+        assert_eq!(areas.target(17, 1), Some(Target::Tab(2)));
+        assert_eq!(areas.target(22, 1), None);
+    }
+
+    #[test]
+    fn finds_rows_inside_the_borders() {
+        let mut areas = areas();
+        assert_eq!(areas.target(5, 3), None);
+        assert_eq!(areas.target(5, 4), Some(Target::Topic(0)));
+        assert_eq!(areas.target(5, 11), Some(Target::Topic(7)));
+        assert_eq!(areas.target(5, 12), None);
+
+        // The Items header is not a row.
+        assert_eq!(areas.target(30, 4), None);
+        assert_eq!(areas.target(30, 5), Some(Target::Item(0)));
+        assert_eq!(areas.target(30, 11), Some(Target::Item(6)));
+        assert_eq!(areas.target(30, 12), None);
+
+        areas.topic_offset = 10;
+        areas.item_offset = 3;
+        assert_eq!(areas.target(5, 4), Some(Target::Topic(10)));
+        assert_eq!(areas.target(30, 5), Some(Target::Item(3)));
+
+        areas.clear();
+        assert_eq!(areas.target(5, 4), None);
+        assert_eq!(areas.target(30, 5), None);
+    }
+
+    #[test]
+    fn finds_text_areas() {
+        let areas = Areas {
+            text_areas: vec![Rect::new(0, 3, 50, 5), Rect::new(0, 8, 50, 5)],
+            ..Areas::default()
+        };
+        assert_eq!(areas.target(10, 3), Some(Target::TextArea(0)));
+        assert_eq!(areas.target(49, 12), Some(Target::TextArea(1)));
+        assert_eq!(areas.target(50, 12), None);
+    }
+
+    #[test]
+    fn double_clicks_need_the_same_target() {
+        let mut areas = Areas::default();
+        assert!(!areas.click(Target::Item(1)));
+        assert!(!areas.click(Target::Item(2)));
+        assert!(areas.click(Target::Item(2)));
+        // A third click starts over rather than counting as another double click.
+        assert!(!areas.click(Target::Item(2)));
+
+        areas.last_click = Some((Instant::now() - DOUBLE_CLICK, Target::Item(2)));
+        assert!(!areas.click(Target::Item(2)));
+    }
+
+    #[test]
+    fn scrolls_like_tui() {
+        assert_eq!(scroll_offset(0, None, 0, 5), 0);
+        assert_eq!(scroll_offset(0, Some(4), 10, 5), 0);
+        assert_eq!(scroll_offset(0, Some(5), 10, 5), 1);
+        assert_eq!(scroll_offset(3, Some(5), 10, 5), 3);
+        assert_eq!(scroll_offset(3, Some(1), 10, 5), 1);
+        // Lists that shrank keep their selection on screen.
+        assert_eq!(scroll_offset(8, Some(9), 3, 5), 2);
+        assert_eq!(scroll_offset(0, Some(2), 10, 0), 2);
+    }
+}
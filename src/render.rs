@@ -1,29 +1,41 @@
-use rusqlite::Connection;
-
 use tui::{
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
+    layout::{Alignment, Constraint, Rect},
+    style::{Modifier, Style},
     text::{Span, Spans},
     widgets::{
         Block, BorderType, Borders, Cell, List, ListItem, ListState, Paragraph, Row, Table,
-        TableState,
+        TableState, Wrap,
     },
 };
 
-use super::db::{read_items_from_db, read_topics_from_db};
-use super::{Item, Topic};
-use crate::{ActiveBlock, Confirm};
+use finish_it::{Item, Topic};
+
+use crate::add::Confirm;
+use crate::app::App;
+use crate::keymap::{Action, KeyContext, KeyMap};
+use crate::mouse::Areas;
+use crate::{ActiveBlock, Theme};
 
-pub fn render_home<'a>() -> Paragraph<'a> {
+/// Below this the screen shows a "terminal too small" message instead of the tabs.
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 16;
+/// Narrower than this the Topics list goes above the Items table, which drops some columns.
+pub const COMPACT_WIDTH: u16 = 100;
+/// Popups never get smaller than this, unless the terminal is.
+const POPUP_MIN_WIDTH: u16 = 40;
+const POPUP_MIN_HEIGHT: u16 = 10;
+
+pub fn render_home<'a>(keymap: &KeyMap, theme: &Theme) -> Paragraph<'a> {
+    let tab = |name: &str, action: Action| {
+        Span::styled(
+            format!("{name} tab ({})", keymap.describe(action, KeyContext::Home)),
+            theme.accent().add_modifier(Modifier::BOLD),
+        )
+    };
     let home = Paragraph::new(vec![
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw("")]),
-        Spans::from(vec![Span::styled(
-            "Finish It!",
-            Style::default()
-                .fg(Color::LightRed)
-                .add_modifier(Modifier::BOLD),
-        )]),
+        Spans::from(vec![Span::styled("Finish It!", theme.title())]),
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![Span::raw(
             "A TUI application to track your progress in any one time or recurring task.",
@@ -31,34 +43,25 @@ pub fn render_home<'a>() -> Paragraph<'a> {
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![
             Span::raw("Use the "),
-            Span::styled(
-                "Topics tab (Alt+t or Home)",
-                Style::default()
-                    .fg(Color::LightYellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
+            tab("Topics", Action::GoTopics),
             Span::raw(" to track, modify or delete your Topics and Items"),
         ]),
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![
             Span::raw("Use the "),
-            Span::styled(
-                "Add tab (Alt+a or Insert)",
-                Style::default()
-                    .fg(Color::LightYellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
+            tab("Add", Action::GoAdd),
             Span::raw(" to add new Topics and/or Items"),
         ]),
         Spans::from(vec![Span::raw("")]),
         Spans::from(vec![
             Span::raw("Use the "),
-            Span::styled(
-                "Quit tab (Alt+q or End)",
-                Style::default()
-                    .fg(Color::LightYellow)
-                    .add_modifier(Modifier::BOLD),
-            ),
+            tab("Activity", Action::GoActivity),
+            Span::raw(" to see what you have done over the last year"),
+        ]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![
+            Span::raw("Use the "),
+            tab("Quit", Action::Quit),
             Span::raw(" to quit Finish It"),
         ]),
     ])
@@ -66,33 +69,36 @@ pub fn render_home<'a>() -> Paragraph<'a> {
     .block(
         Block::default()
             .borders(Borders::ALL)
-            .style(Style::default().fg(Color::White))
+            .style(theme.text())
             .title("Home")
             .border_type(BorderType::Plain),
     );
     home
 }
 
+/// Builds the Topics list and the Items table from the rows `areas` says they are scrolled to,
+/// with states selecting the right row among them. tui keeps its own scroll offset private, so
+/// it can't be handed in. Only the rows that fit are built.
 pub fn render_topics<'a>(
-    event_list_state: &ListState,
-    item_list_state: &TableState,
-    conn: &Connection,
-    active_block: &ActiveBlock,
-) -> (List<'a>, Item, Topic, Table<'a>) {
-    let (list_highlight, table_highlight) = match active_block {
-        ActiveBlock::Event => (Color::Red, Color::Yellow),
-        ActiveBlock::InstanceBlock => (Color::Yellow, Color::Red),
-    };
+    app: &App,
+    areas: &Areas,
+    compact: bool,
+    theme: &Theme,
+) -> (List<'a>, ListState, Table<'a>, TableState) {
+    let state = &app.state;
+    let bar_width = app.config.bar_width;
+    let topics_focused = app.active_block == ActiveBlock::Event;
     let topics = Block::default()
         .borders(Borders::ALL)
-        .style(Style::default().fg(Color::White))
+        .style(theme.text())
         .title("Topics")
         .border_type(BorderType::Plain);
 
-    let event_list = read_topics_from_db(conn).expect("can fetch Topic list");
-
-    let items: Vec<_> = event_list
+    let items: Vec<_> = state
+        .topics
         .iter()
+        .skip(areas.topic_offset)
+        .take(areas.topics.height.saturating_sub(2) as usize)
         .map(|item| {
             ListItem::new(Spans::from(vec![Span::styled(
                 item.name.clone(),
@@ -101,129 +107,376 @@ pub fn render_topics<'a>(
         })
         .collect();
 
-    let selected_event = event_list
-        .get(event_list_state.selected().unwrap_or(0))
-        .unwrap_or(&Topic::default())
-        .clone();
-
-    let list = List::new(items).block(topics).highlight_style(
-        Style::default()
-            .bg(list_highlight)
-            .fg(Color::Black)
-            .add_modifier(Modifier::BOLD),
-    );
-
-    let item_list = read_items_from_db(conn, &selected_event.name).expect("can fetch Topic list");
-
-    let selected_item = match item_list_state.selected() {
-        Some(i) => match item_list.get(i) {
-            Some(inst) => inst.to_owned(),
-            None => Item::default(),
-        },
-        None => Item::default(),
-    };
+    let list = List::new(items)
+        .block(topics)
+        .highlight_style(theme.selection(topics_focused));
 
     let mut rows: Vec<Row<'a>> = Vec::new();
-    for item in item_list {
-        // rows.push(Row::new(item.as_cells()));
-        rows.push(Row::new(vec![
-            Cell::from(Span::raw(item.id.to_string())),
-            Cell::from(Span::raw(item.name.to_string())),
-            Cell::from(Span::raw(item.get_dot_vec())),
-            Cell::from(Span::raw(Confirm::get_confirm_str(
-                &item.isrecurring.to_string(),
-            ))),
-            Cell::from(Span::raw(item.percentage.to_string())),
-            Cell::from(Span::raw(item.timesfinished.to_string())),
-            Cell::from(Span::raw(item.days_left())),
-            Cell::from(Span::raw(item.created.date_naive().to_string())),
-        ]));
+    let visible_items = areas.items.height.saturating_sub(3) as usize;
+    for item in state
+        .items
+        .iter()
+        .skip(areas.item_offset)
+        .take(visible_items)
+    {
+        let forecast = match state.forecasts.get(&item.id) {
+            Some(forecast) => *forecast,
+            None => item.forecast(&state.history),
+        };
+        let eta_style = match forecast.late {
+            true => theme.error(),
+            false => Style::default(),
+        };
+        let eta = Cell::from(Span::styled(item.eta_str(&forecast), eta_style));
+        let cells = match compact {
+            true => vec![
+                Cell::from(Span::raw(item.name.to_string())),
+                Cell::from(Span::raw(item.get_dot_vec(bar_width))),
+                Cell::from(Span::raw(item.percentage.to_string())),
+                Cell::from(Span::raw(item.days_left())),
+                eta,
+            ],
+            false => vec![
+                Cell::from(Span::raw(item.id.to_string())),
+                Cell::from(Span::raw(item.name.to_string())),
+                Cell::from(Span::raw(item.get_dot_vec(bar_width))),
+                Cell::from(Span::raw(Confirm::get_confirm_str(
+                    &item.isrecurring.to_string(),
+                ))),
+                Cell::from(Span::raw(item.percentage.to_string())),
+                Cell::from(Span::raw(item.timesfinished.to_string())),
+                Cell::from(Span::raw(item.days_left())),
+                eta,
+                Cell::from(Span::raw(item.created.date_naive().to_string())),
+            ],
+        };
+        rows.push(Row::new(cells));
     }
 
-    let item_detail = Table::new(rows)
-        .header(Row::new(vec![
-            Cell::from(Span::styled(
+    // The compact table keeps what is needed to pick an Item and see how it is going.
+    let (header, widths): (&[&str], &[Constraint]) = match compact {
+        true => (
+            &["Name", "Progress", "%", "Day Limit", "ETA"],
+            &[
+                Constraint::Percentage(30),
+                Constraint::Percentage(25),
+                Constraint::Percentage(10),
+                Constraint::Percentage(15),
+                Constraint::Percentage(20),
+            ],
+        ),
+        false => (
+            &[
                 "ID",
-                Style::default().add_modifier(Modifier::BOLD),
-            )),
-            Cell::from(Span::styled(
                 "Name",
-                Style::default().add_modifier(Modifier::BOLD),
-            )),
-            Cell::from(Span::styled(
                 "Progress",
-                Style::default().add_modifier(Modifier::BOLD),
-            )),
-            Cell::from(Span::styled(
                 "Recurring?",
-                Style::default().add_modifier(Modifier::BOLD),
-            )),
-            Cell::from(Span::styled(
                 "Completed %",
-                Style::default().add_modifier(Modifier::BOLD),
-            )),
-            Cell::from(Span::styled(
                 "Completed #",
+                "Day Limit",
+                "ETA",
+                "Created At",
+            ],
+            &[
+                Constraint::Percentage(5),
+                Constraint::Percentage(20),
+                Constraint::Percentage(15),
+                Constraint::Percentage(8),
+                Constraint::Percentage(8),
+                Constraint::Percentage(8),
+                Constraint::Percentage(8),
+                Constraint::Percentage(15),
+                Constraint::Percentage(13),
+            ],
+        ),
+    };
+
+    let item_detail = Table::new(rows)
+        .header(Row::new(header.iter().map(|title| {
+            Cell::from(Span::styled(
+                *title,
                 Style::default().add_modifier(Modifier::BOLD),
-            )),
+            ))
+        })))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(theme.text())
+                .title("Items")
+                .border_type(BorderType::Plain),
+        )
+        .widths(widths)
+        .highlight_style(theme.selection(!topics_focused));
+
+    let mut list_state = ListState::default();
+    list_state.select(
+        app.topic_list_state
+            .selected()
+            .map(|i| i.saturating_sub(areas.topic_offset)),
+    );
+    let mut table_state = TableState::default();
+    table_state.select(
+        app.item_list_state
+            .selected()
+            .map(|i| i.saturating_sub(areas.item_offset)),
+    );
+
+    (list, list_state, item_detail, table_state)
+}
+
+/// The Update popup, where the progress of `item` is changed before saving it.
+pub fn render_update_popup<'a>(
+    item: &Item,
+    bar_width: usize,
+    keymap: &KeyMap,
+    theme: &Theme,
+) -> Paragraph<'a> {
+    let keys = |action| keymap.describe(action, KeyContext::UpdatePopup);
+    let text = vec![
+        Spans::from(vec![Span::raw(item.topicname.to_owned())]),
+        Spans::from(vec![Span::raw(item.name.to_owned())]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::raw(format!(
+            "{} : {:.1}",
+            item.get_dot_vec(bar_width),
+            item.percentage
+        ))]),
+        Spans::from(vec![Span::raw(format!(
+            "Is Recurring? = {}  Times Finished = {}",
+            item.isrecurring, item.timesfinished
+        ))]),
+        Spans::from(vec![Span::raw(format!(
+            "Change Progress with {} and {}. Press {} to Complete Item for full progression.",
+            keys(Action::Decrement),
+            keys(Action::Increment),
+            keys(Action::FinishOnce)
+        ))]),
+        Spans::from(vec![Span::raw(format!(
+            "Press {} to Update The Progress. Press {} to Cancel.",
+            keys(Action::Confirm),
+            keys(Action::Cancel)
+        ))]),
+    ];
+
+    Paragraph::new(text)
+        .style(theme.popup())
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(theme.text())
+                .title("Update Progress")
+                .border_type(BorderType::Plain),
+        )
+}
+
+/// How to answer the Delete popups.
+fn confirm_hint(keymap: &KeyMap) -> String {
+    format!(
+        "Hit {} to Confirm or {} to Cancel",
+        keymap.describe(Action::Confirm, KeyContext::DeletePopup),
+        keymap.describe(Action::Cancel, KeyContext::DeletePopup)
+    )
+}
+
+/// Asks to confirm deleting `item`.
+pub fn render_delete_item_popup<'a>(item: &Item, keymap: &KeyMap, theme: &Theme) -> Paragraph<'a> {
+    let text = vec![
+        Spans::from(vec![Span::raw("Are you sure you want to DELETE:")]),
+        Spans::from(vec![Span::raw(item.name.to_owned())]),
+        Spans::from(vec![Span::raw(confirm_hint(keymap))]),
+    ];
+
+    Paragraph::new(text)
+        .style(theme.popup())
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(theme.text())
+                .title("Delete Item")
+                .border_type(BorderType::Plain),
+        )
+}
+
+/// Asks to confirm deleting `topic` and its Items.
+pub fn render_delete_topic_popup<'a>(
+    topic: &Topic,
+    keymap: &KeyMap,
+    theme: &Theme,
+) -> Paragraph<'a> {
+    let text = vec![
+        Spans::from(vec![Span::raw(format!(
+            "Are you sure you want to DELETE Topic {} and ALL ITEMS belonging to this Topic",
+            topic.name
+        ))]),
+        Spans::from(vec![Span::raw(confirm_hint(keymap))]),
+    ];
+
+    Paragraph::new(text)
+        .style(theme.popup())
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(theme.text())
+                .title("Delete Topic")
+                .border_type(BorderType::Plain),
+        )
+}
+
+/// Lists the profiles to switch to, marking the active one.
+pub fn render_profiles_popup<'a>(profiles: &[String], active: &str, theme: &Theme) -> List<'a> {
+    let items: Vec<ListItem> = profiles
+        .iter()
+        .map(|name| {
+            let text = match name == active {
+                true => format!("{name} (active)"),
+                false => name.clone(),
+            };
+            ListItem::new(Spans::from(vec![Span::raw(text)]))
+        })
+        .collect();
+
+    List::new(items)
+        .style(theme.popup())
+        .highlight_style(theme.selection(true))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(theme.text())
+                .title("Switch Profile")
+                .border_type(BorderType::Plain),
+        )
+}
+
+/// Shows an error message in place of a widget or as a popup.
+pub fn render_error<'a>(message: &str, hint: &str, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(vec![
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::styled(message.to_owned(), theme.error())]),
+        Spans::from(vec![Span::raw("")]),
+        Spans::from(vec![Span::raw(hint.to_owned())]),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
+    .block(
+        Block::default()
+            .borders(Borders::ALL)
+            .style(theme.text())
+            .title("Error")
+            .border_type(BorderType::Plain),
+    )
+}
+
+/// Shown instead of everything else while the terminal is below `MIN_WIDTH` x `MIN_HEIGHT`.
+pub fn render_too_small<'a>(size: Rect, theme: &Theme) -> Paragraph<'a> {
+    Paragraph::new(vec![
+        Spans::from(vec![Span::styled("Terminal too small", theme.error())]),
+        Spans::from(vec![Span::raw(format!(
+            "{}x{}, needs {MIN_WIDTH}x{MIN_HEIGHT}",
+            size.width, size.height
+        ))]),
+    ])
+    .alignment(Alignment::Center)
+    .wrap(Wrap { trim: true })
+    .style(theme.text())
+}
+
+/// Lists the keys of `context` straight from the key map, so it matches what the keys do.
+pub fn render_help<'a>(keymap: &KeyMap, context: KeyContext, theme: &Theme) -> Table<'a> {
+    let rows: Vec<Row> = keymap
+        .help(context)
+        .into_iter()
+        .map(|(keys, description)| {
+            Row::new(vec![
+                Cell::from(Span::styled(keys, theme.accent())),
+                Cell::from(Span::raw(description)),
+            ])
+        })
+        .collect();
+
+    Table::new(rows)
+        .header(Row::new(vec![
             Cell::from(Span::styled(
-                "Day Limit",
+                "Keys",
                 Style::default().add_modifier(Modifier::BOLD),
             )),
             Cell::from(Span::styled(
-                "Created At",
+                "Action",
                 Style::default().add_modifier(Modifier::BOLD),
             )),
         ]))
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .style(Style::default().fg(Color::White))
-                .title("Items")
+                .style(theme.popup())
+                .title(format!(
+                    "Keys - {} ({} or {} to close)",
+                    context.title(),
+                    keymap.describe(Action::Cancel, KeyContext::Help),
+                    keymap.describe(Action::Help, KeyContext::Help)
+                ))
                 .border_type(BorderType::Plain),
         )
-        .widths(&[
-            Constraint::Percentage(5),
-            Constraint::Percentage(25),
-            Constraint::Percentage(15),
-            Constraint::Percentage(10),
-            Constraint::Percentage(10),
-            Constraint::Percentage(10),
-            Constraint::Percentage(10),
-            Constraint::Percentage(15),
-        ])
-        .highlight_style(
-            Style::default()
-                .bg(table_highlight)
-                .fg(Color::Black)
-                .add_modifier(Modifier::BOLD),
-        );
-
-    (list, selected_item, selected_event, item_detail)
+        .widths(&[Constraint::Percentage(40), Constraint::Percentage(60)])
+}
+
+/// `percent` percent of `length`, multiplied in u32 as wide terminals overflow u16.
+fn percent_of(length: u16, percent: u16) -> u16 {
+    u16::try_from(u32::from(length) * u32::from(percent) / 100).unwrap_or(u16::MAX)
 }
 
+/// A rect in the middle of `r` taking the given percentages of it, but at least
+/// `POPUP_MIN_WIDTH` x `POPUP_MIN_HEIGHT` so small terminals don't clip popup text.
 pub fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Percentage((100 - percent_y) / 2),
-                Constraint::Percentage(percent_y),
-                Constraint::Percentage((100 - percent_y) / 2),
-            ]
-            .as_ref(),
-        )
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(
-            [
-                Constraint::Percentage((100 - percent_x) / 2),
-                Constraint::Percentage(percent_x),
-                Constraint::Percentage((100 - percent_x) / 2),
-            ]
-            .as_ref(),
-        )
-        .split(popup_layout[1])[1]
+    let width = percent_of(r.width, percent_x)
+        .max(POPUP_MIN_WIDTH)
+        .min(r.width);
+    let height = percent_of(r.height, percent_y)
+        .max(POPUP_MIN_HEIGHT)
+        .min(r.height);
+    Rect::new(
+        r.x + (r.width - width) / 2,
+        r.y + (r.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn popups_are_centered() {
+        let area = Rect::new(0, 0, 100, 50);
+        assert_eq!(centered_rect(60, 40, area), Rect::new(20, 15, 60, 20));
+
+        let offset = Rect::new(10, 5, 100, 50);
+        assert_eq!(centered_rect(60, 40, offset), Rect::new(30, 20, 60, 20));
+    }
+
+    #[test]
+    fn popups_keep_their_minimum_size_within_the_area() {
+        let small = Rect::new(0, 0, 30, 8);
+        let popup = centered_rect(10, 10, small);
+        assert_eq!(popup.width, POPUP_MIN_WIDTH.min(30));
+        assert_eq!(popup.height, POPUP_MIN_HEIGHT.min(8));
+        assert!(popup.x + popup.width <= 30 && popup.y + popup.height <= 8);
+    }
+
+    #[test]
+    fn wide_terminals_do_not_overflow() {
+        // 1100 * 60 no longer fits u16.
+        let wide = Rect::new(0, 0, 1100, 59);
+        assert_eq!(centered_rect(60, 40, wide), Rect::new(220, 18, 660, 23));
+
+        let widest = Rect::new(0, 0, u16::MAX, 1);
+        let popup = centered_rect(100, 50, widest);
+        assert_eq!(popup, Rect::new(0, 0, u16::MAX, 1));
+    }
 }
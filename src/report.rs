@@ -0,0 +1,424 @@
+//! Reviews of what happened to the Items over a period of time.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+
+use chrono::{prelude::*, Duration};
+use serde::Serialize;
+
+use crate::error::StoreError;
+use crate::model::{History, Item};
+use crate::store::Store;
+
+/// How `Report::render` writes a Report.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReportFormat {
+    /// Plain text, as shown in the Review popup.
+    Text,
+    /// Markdown with a heading per section.
+    Markdown,
+    /// Pretty printed JSON.
+    Json,
+}
+
+impl std::str::FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<ReportFormat, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "text" | "txt" => Ok(ReportFormat::Text),
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "json" => Ok(ReportFormat::Json),
+            other => Err(format!(
+                "unknown report format '{other}', expected text, markdown or json"
+            )),
+        }
+    }
+}
+
+/// Longest period a Report looks back, about a century.
+const MAX_PERIOD_DAYS: i64 = 36_500;
+
+/// Parses a period like `7d`, `2w` or `1m` (30 days) into a Duration.
+pub fn parse_period(input: &str) -> Result<Duration, String> {
+    let err = || format!("invalid period '{input}', expected a number followed by d, w or m");
+    let (split, _) = input.char_indices().last().ok_or_else(err)?;
+    let (amount, unit) = input.split_at(split);
+    let amount: i64 = amount.parse().map_err(|_| err())?;
+    if amount <= 0 {
+        return Err(format!("invalid period '{input}', expected more than 0"));
+    }
+    let days_per_unit = match unit {
+        "d" => 1,
+        "w" => 7,
+        "m" => 30,
+        _ => return Err(err()),
+    };
+    match amount.checked_mul(days_per_unit) {
+        Some(days) if days <= MAX_PERIOD_DAYS => Ok(Duration::days(days)),
+        _ => Err(format!(
+            "invalid period '{input}', expected at most {MAX_PERIOD_DAYS} days"
+        )),
+    }
+}
+
+/// An Item as it appears in one of the sections of a Report.
+#[derive(Serialize, Debug, Clone)]
+pub struct ReportEntry {
+    id: usize,
+    topic: String,
+    name: String,
+    /// Progress made in the period in percentage points.
+    delta: i64,
+    percentage: u8,
+    timesfinished: usize,
+}
+
+impl ReportEntry {
+    fn from_item(item: &Item, delta: i64) -> Self {
+        Self {
+            id: item.id,
+            topic: item.topicname.to_owned(),
+            name: item.name.to_owned(),
+            delta,
+            percentage: item.percentage,
+            timesfinished: item.timesfinished,
+        }
+    }
+
+    fn from_history(entry: &History) -> Self {
+        Self {
+            id: entry.itemid,
+            topic: entry.topicname.to_owned(),
+            name: entry.itemname.to_owned(),
+            delta: entry.delta,
+            percentage: entry.percentage,
+            timesfinished: entry.timesfinished,
+        }
+    }
+
+    fn line(&self) -> String {
+        let mut line = format!("{} / {} ({}%", self.topic, self.name, self.percentage);
+        if self.timesfinished > 0 {
+            let _ = write!(line, ", finished {} times", self.timesfinished);
+        }
+        line.push(')');
+        if self.delta != 0 {
+            let _ = write!(line, " {:+}", self.delta);
+        }
+        line
+    }
+}
+
+/// The Items finished, progressed, added, deleted, abandoned, overdue or missed in a period.
+#[derive(Serialize, Debug, Clone)]
+pub struct Report {
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+    finished: Vec<ReportEntry>,
+    progressed: Vec<ReportEntry>,
+    new: Vec<ReportEntry>,
+    deleted: Vec<ReportEntry>,
+    abandoned: Vec<ReportEntry>,
+    overdue: Vec<ReportEntry>,
+    missed: Vec<ReportEntry>,
+}
+
+impl Report {
+    fn sections(&self) -> [(&'static str, &Vec<ReportEntry>); 7] {
+        [
+            ("Finished", &self.finished),
+            ("Progressed", &self.progressed),
+            ("New", &self.new),
+            ("Deleted", &self.deleted),
+            ("Abandoned (no progress in the period)", &self.abandoned),
+            ("Overdue", &self.overdue),
+            ("Recurring habits missed", &self.missed),
+        ]
+    }
+
+    /// The Report as plain text, one line per Item.
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "Review {} - {}\n",
+            self.since.with_timezone(&Local).date_naive(),
+            self.until.with_timezone(&Local).date_naive()
+        );
+        for (title, entries) in self.sections() {
+            let _ = write!(out, "\n{} ({})\n", title, entries.len());
+            for entry in entries {
+                let _ = writeln!(out, "  - {}", entry.line());
+            }
+        }
+        out
+    }
+
+    /// The Report as a Markdown document.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# Review {} - {}\n",
+            self.since.with_timezone(&Local).date_naive(),
+            self.until.with_timezone(&Local).date_naive()
+        );
+        for (title, entries) in self.sections() {
+            let _ = write!(out, "\n## {} ({})\n\n", title, entries.len());
+            if entries.is_empty() {
+                out.push_str("_Nothing._\n");
+            }
+            for entry in entries {
+                let _ = writeln!(out, "- {}", entry.line());
+            }
+        }
+        out
+    }
+
+    /// The Report in `format`.
+    pub fn render(&self, format: ReportFormat) -> Result<String, serde_json::Error> {
+        match format {
+            ReportFormat::Text => Ok(self.to_text()),
+            ReportFormat::Markdown => Ok(self.to_markdown()),
+            ReportFormat::Json => serde_json::to_string_pretty(self),
+        }
+    }
+}
+
+/// How long a recurring Item may go without progress before it counts as missed: its day limit,
+/// or a day without one.
+fn cadence(item: &Item) -> Duration {
+    Duration::days(item.daylimit.clamp(1, MAX_PERIOD_DAYS as usize) as i64)
+}
+
+/// Collects what happened to every Item between `since` and now.
+pub fn build_report(store: &dyn Store, since: DateTime<Utc>) -> Result<Report, StoreError> {
+    build_report_until(store, since, Utc::now())
+}
+
+fn build_report_until(
+    store: &dyn Store,
+    since: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Result<Report, StoreError> {
+    let items = store.all_items()?;
+    // Habits repeating less often than the period is long are judged on their whole last round,
+    // which can start before the period does.
+    let habits_since = items
+        .iter()
+        .filter(|item| item.isrecurring == 1)
+        .map(|item| until - cadence(item))
+        .fold(since, DateTime::min);
+    let history = store.history(habits_since)?;
+
+    let mut per_item: BTreeMap<usize, Vec<&History>> = BTreeMap::new();
+    let mut last_progress: HashMap<usize, DateTime<Utc>> = HashMap::new();
+    for entry in history.iter().filter(|entry| entry.action == "update") {
+        if entry.delta > 0 {
+            last_progress.insert(entry.itemid, entry.created);
+        }
+        if entry.created >= since {
+            per_item.entry(entry.itemid).or_default().push(entry);
+        }
+    }
+    let items_by_id: HashMap<usize, &Item> = items.iter().map(|item| (item.id, item)).collect();
+
+    let mut report = Report {
+        since,
+        until,
+        finished: Vec::new(),
+        progressed: Vec::new(),
+        new: Vec::new(),
+        deleted: Vec::new(),
+        abandoned: Vec::new(),
+        overdue: Vec::new(),
+        missed: Vec::new(),
+    };
+
+    for (id, entries) in per_item.iter() {
+        let item = match items_by_id.get(id) {
+            Some(item) => *item,
+            None => continue,
+        };
+        let delta: i64 = entries.iter().map(|entry| entry.delta).sum();
+        let after = item.progress_points();
+        let before = after - delta;
+        let finished = match item.isrecurring {
+            1 => after.div_euclid(100) > before.div_euclid(100),
+            _ => before < 100 && after >= 100,
+        };
+        if finished {
+            report.finished.push(ReportEntry::from_item(item, delta));
+        }
+        if delta != 0 {
+            report.progressed.push(ReportEntry::from_item(item, delta));
+        }
+    }
+
+    for item in items.iter() {
+        let active = per_item
+            .get(&item.id)
+            .is_some_and(|entries| entries.iter().any(|entry| entry.delta > 0));
+        if item.created >= since {
+            report.new.push(ReportEntry::from_item(item, 0));
+            continue;
+        }
+        if active {
+            continue;
+        }
+        match (item.isrecurring, item.is_done()) {
+            (1, _) => {
+                let round_start = since.min(until - cadence(item));
+                let missed = item.created < round_start
+                    && last_progress
+                        .get(&item.id)
+                        .is_none_or(|&last| last < round_start);
+                if missed {
+                    report.missed.push(ReportEntry::from_item(item, 0));
+                }
+            }
+            (_, false) => report.abandoned.push(ReportEntry::from_item(item, 0)),
+            _ => {}
+        }
+    }
+
+    for item in items.iter() {
+        if let Some(deadline) = item.deadline() {
+            if deadline < until && !item.is_done() && item.isrecurring == 0 {
+                report.overdue.push(ReportEntry::from_item(item, 0));
+            }
+        }
+    }
+
+    report.deleted = history
+        .iter()
+        .filter(|entry| entry.action == "delete" && entry.created >= since)
+        .map(ReportEntry::from_history)
+        .collect();
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memory::MemoryStore;
+
+    fn names(entries: &[ReportEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    /// Items added a month before `now`, with the progress made at `now`.
+    fn seed(now: DateTime<Utc>) -> MemoryStore {
+        let store = MemoryStore::new();
+        let item = |id, name: &str, isrecurring, percentage, daylimit| Item {
+            id,
+            name: name.to_string(),
+            topicname: String::from("Life"),
+            isrecurring,
+            percentage,
+            daylimit,
+            created: now - Duration::days(30),
+            ..Item::default()
+        };
+        store
+            .import_items(&[
+                item(1, "Run", 1, 0, 1),
+                item(2, "Gym", 1, 0, 7),
+                item(3, "Stretch", 1, 0, 1),
+                item(4, "Piano", 1, 0, 7),
+                item(5, "Read SICP", 0, 40, 0),
+                item(6, "Read TAOCP", 0, 10, 0),
+                item(7, "Taxes", 0, 0, 10),
+                item(8, "Old", 0, 0, 0),
+                Item {
+                    created: now - Duration::days(1),
+                    ..item(9, "Yoga", 1, 0, 7)
+                },
+            ])
+            .unwrap();
+        let items = store.all_items().unwrap();
+        for (id, percentage) in [(1, 50), (2, 20), (5, 100)] {
+            let item = items.iter().find(|item| item.id == id).unwrap();
+            store
+                .update_item(&Item {
+                    percentage,
+                    ..item.clone()
+                })
+                .unwrap();
+        }
+        store.delete_item(&items[7]).unwrap();
+        store
+    }
+
+    #[test]
+    fn reports_what_happened_in_the_period() {
+        let now = Utc::now();
+        let store = seed(now);
+        let report =
+            build_report_until(&store, now - Duration::hours(1), now + Duration::hours(1)).unwrap();
+
+        assert_eq!(names(&report.finished), ["Read SICP"]);
+        assert_eq!(names(&report.progressed), ["Run", "Gym", "Read SICP"]);
+        let deltas: Vec<i64> = report.progressed.iter().map(|entry| entry.delta).collect();
+        assert_eq!(deltas, [50, 20, 60]);
+        assert_eq!(names(&report.new), [] as [&str; 0]);
+        assert_eq!(names(&report.deleted), ["Old"]);
+        assert_eq!(names(&report.abandoned), ["Read TAOCP", "Taxes"]);
+        assert_eq!(names(&report.overdue), ["Taxes"]);
+        assert_eq!(names(&report.missed), ["Stretch", "Piano"]);
+
+        let since_yesterday = build_report_until(&store, now - Duration::days(2), now).unwrap();
+        assert_eq!(names(&since_yesterday.new), ["Yoga"]);
+    }
+
+    #[test]
+    fn habits_are_missed_after_a_whole_round_without_progress() {
+        let now = Utc::now();
+        let store = seed(now);
+        // A daily report two days later: the daily habit is a round behind, the weekly one
+        // was done this week. Yoga is weekly and only a day older than the progress.
+        let report =
+            build_report_until(&store, now + Duration::days(2), now + Duration::days(3)).unwrap();
+        assert_eq!(names(&report.missed), ["Run", "Stretch", "Piano"]);
+        assert!(report.progressed.is_empty());
+        assert!(report.deleted.is_empty());
+
+        // Ten days later the weekly habits are missed too, Yoga included.
+        let report =
+            build_report_until(&store, now + Duration::days(9), now + Duration::days(10)).unwrap();
+        assert_eq!(
+            names(&report.missed),
+            ["Run", "Gym", "Stretch", "Piano", "Yoga"]
+        );
+    }
+
+    #[test]
+    fn parses_periods() {
+        assert_eq!(parse_period("7d"), Ok(Duration::days(7)));
+        assert_eq!(parse_period("2w"), Ok(Duration::days(14)));
+        assert_eq!(parse_period("1m"), Ok(Duration::days(30)));
+        assert_eq!(parse_period("36500d"), Ok(Duration::days(MAX_PERIOD_DAYS)));
+    }
+
+    #[test]
+    fn rejects_invalid_periods() {
+        for input in [
+            "",
+            "d",
+            "7",
+            "7x",
+            "7 d",
+            "d7",
+            "1.5w",
+            "0d",
+            "-3d",
+            "+0w",
+            "7é",
+            "é",
+            "日",
+            "3日",
+            "36501d",
+            "9223372036854775807m",
+        ] {
+            assert!(parse_period(input).is_err(), "{input}");
+        }
+    }
+}
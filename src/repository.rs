@@ -0,0 +1,171 @@
+//! The SQLite database the TUI keeps its Topics, Items and History in.
+
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{Connection, DatabaseName, ErrorCode, OpenFlags};
+
+use crate::db;
+use crate::error::StoreError;
+use crate::model::{History, Item, Topic};
+use crate::store::{check_item, Store};
+
+/// A finish-it database, read and changed through its [`Store`] implementation.
+///
+/// Every change made through it is logged in the History the same way the TUI logs it, so
+/// tools built on the library and the TUI can share a database.
+pub struct Repository {
+    conn: Connection,
+    /// `None` for databases in memory.
+    path: Option<PathBuf>,
+    removed_orphans: Vec<Item>,
+}
+
+impl Repository {
+    /// Opens the database at `path`, creating it and its tables if needed.
+    ///
+    /// Databases made by older versions are upgraded on the way, see `removed_orphans`.
+    pub fn open(path: &Path) -> Result<Self, rusqlite::Error> {
+        let conn = db::get_db_connection(path)?;
+        let removed_orphans = db::migrate(&conn)?;
+        Ok(Self {
+            conn,
+            path: Some(path.to_path_buf()),
+            removed_orphans,
+        })
+    }
+
+    /// Opens an empty database that lives in memory until it is dropped.
+    pub fn open_in_memory() -> Result<Self, rusqlite::Error> {
+        Ok(Self {
+            conn: db::get_db_connection(Path::new(":memory:"))?,
+            path: None,
+            removed_orphans: Vec::new(),
+        })
+    }
+
+    /// The file the database is kept in, `None` if it lives in memory.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Items that opening the database removed because their Topic had been deleted without
+    /// them. Only databases made by older versions can have any, and only the first time they
+    /// are opened.
+    pub fn removed_orphans(&self) -> &[Item] {
+        &self.removed_orphans
+    }
+
+    /// The underlying connection, for queries the Repository has no method for.
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Replaces the whole database with the finish-it database at `path`, upgrading it if it
+    /// was made by an older version. Returns the Items the upgrade removed, like `open`.
+    ///
+    /// The copy is upgraded next to the database first. If that fails the database is left as
+    /// it was.
+    pub fn restore_from(&self, path: &Path) -> Result<Vec<Item>, rusqlite::Error> {
+        let own = match &self.path {
+            Some(own) => own,
+            None => return Err(rusqlite::Error::InvalidPath(path.to_path_buf())),
+        };
+        let source = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        // Fails on files that are not SQLite databases, or not ours.
+        source.prepare("SELECT id, topicname FROM items")?;
+        source.prepare("SELECT name FROM topics")?;
+
+        let mut staging = own.as_os_str().to_owned();
+        staging.push(".restore");
+        let staging = PathBuf::from(staging);
+        let staged = source
+            .backup(DatabaseName::Main, &staging, None)
+            .and_then(|()| {
+                let conn = db::get_db_connection(&staging)?;
+                let removed = db::migrate(&conn)?;
+                Ok((conn, removed))
+            });
+        let result = staged.and_then(|(conn, removed)| {
+            // Writing through a second connection keeps SQLite's locking in charge, so this
+            // one sees the new content once it is done.
+            conn.backup(DatabaseName::Main, own, None)?;
+            Ok(removed)
+        });
+        let _ = std::fs::remove_file(&staging);
+        self.conn.flush_prepared_statement_cache();
+        result
+    }
+}
+
+impl Store for Repository {
+    fn topics(&self) -> Result<Vec<Topic>, StoreError> {
+        Ok(db::read_topics_from_db(&self.conn)?)
+    }
+
+    fn items(&self, topic: &str) -> Result<Vec<Item>, StoreError> {
+        Ok(db::read_items_from_db(&self.conn, topic)?)
+    }
+
+    fn all_items(&self) -> Result<Vec<Item>, StoreError> {
+        Ok(db::read_all_items_from_db(&self.conn)?)
+    }
+
+    fn history(&self, since: DateTime<Utc>) -> Result<Vec<History>, StoreError> {
+        Ok(db::read_history_from_db(&self.conn, since)?)
+    }
+
+    fn topic_history(&self, topic: &str) -> Result<Vec<History>, StoreError> {
+        Ok(db::read_topic_history_from_db(&self.conn, topic)?)
+    }
+
+    fn add_item(&self, item: &Item) -> Result<(), StoreError> {
+        check_item(item)?;
+        Ok(db::add_item(&self.conn, item)?)
+    }
+
+    fn import_items(&self, items: &[Item]) -> Result<usize, StoreError> {
+        items.iter().try_for_each(check_item)?;
+        let tx = self.conn.unchecked_transaction()?;
+        for item in items {
+            db::import_item(&tx, item)?;
+        }
+        tx.commit()?;
+        Ok(items.len())
+    }
+
+    fn update_item(&self, item: &Item) -> Result<(), StoreError> {
+        check_item(item)?;
+        db::update_item(&self.conn, item).map_err(|err| match err {
+            rusqlite::Error::QueryReturnedNoRows => StoreError::NotFound(item.id),
+            err => StoreError::Sqlite(err),
+        })
+    }
+
+    fn delete_item(&self, item: &Item) -> Result<(), StoreError> {
+        Ok(db::delete_item(&self.conn, item)?)
+    }
+
+    fn delete_topic(&self, topic: &Topic) -> Result<(), StoreError> {
+        Ok(db::delete_topic(&self.conn, topic)?)
+    }
+
+    fn data_version(&self) -> Result<i64, StoreError> {
+        Ok(db::data_version(&self.conn)?)
+    }
+}
+
+/// Explains a database error in words a user can act on.
+pub fn describe_error(err: &rusqlite::Error) -> String {
+    match err.sqlite_error_code() {
+        Some(ErrorCode::DatabaseBusy) | Some(ErrorCode::DatabaseLocked) => String::from(
+            "The database is locked by another program. Close it or try again in a moment.",
+        ),
+        Some(ErrorCode::ConstraintViolation) => {
+            format!("The database refused the change: {err}")
+        }
+        Some(ErrorCode::ReadOnly) => String::from("The database is read-only."),
+        Some(ErrorCode::DiskFull) => String::from("The disk is full."),
+        _ => format!("Database error: {err}"),
+    }
+}
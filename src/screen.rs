@@ -0,0 +1,98 @@
+use std::io;
+use std::panic;
+use std::sync::mpsc::Sender;
+
+use crossterm::{
+    cursor,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use crate::Event;
+
+/// Puts the terminal into raw mode on the alternate screen for as long as it lives.
+///
+/// Dropping the guard gives the shell its screen back, also when leaving with an error.
+pub struct TerminalGuard {
+    mouse: bool,
+}
+
+fn enter(mouse: bool) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    if mouse {
+        execute!(io::stdout(), EnableMouseCapture)?;
+    }
+    Ok(())
+}
+
+fn leave(mouse: bool) -> io::Result<()> {
+    if mouse {
+        execute!(io::stdout(), DisableMouseCapture)?;
+    }
+    execute!(io::stdout(), LeaveAlternateScreen, cursor::Show)?;
+    disable_raw_mode()
+}
+
+impl TerminalGuard {
+    pub fn new(mouse: bool) -> io::Result<Self> {
+        // A panic prints its message after the terminal is restored, so it can be read.
+        let default_hook = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            let _ = leave(mouse);
+            default_hook(info);
+        }));
+
+        enter(mouse)?;
+        Ok(Self { mouse })
+    }
+
+    /// Hands the terminal back to the shell and stops the process until it is resumed with
+    /// `fg`. The screen has to be redrawn from scratch afterwards.
+    #[cfg(unix)]
+    pub fn suspend(&self) -> io::Result<()> {
+        leave(self.mouse)?;
+        signal_hook::low_level::raise(signal_hook::consts::SIGSTOP)?;
+        enter(self.mouse)
+    }
+
+    /// Windows has no job control, suspending does nothing there.
+    #[cfg(not(unix))]
+    pub fn suspend(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = leave(self.mouse);
+    }
+}
+
+/// Turns SIGINT and SIGTERM into a clean exit and SIGTSTP into a suspend, sent as Events.
+///
+/// Ctrl+C and Ctrl+Z arrive as keys in raw mode; this covers `kill` and the like.
+#[cfg(unix)]
+pub fn forward_signals<I: Send + 'static>(tx: Sender<Event<I>>) -> io::Result<()> {
+    use signal_hook::consts::{SIGINT, SIGTERM, SIGTSTP};
+
+    let mut signals = signal_hook::iterator::Signals::new([SIGINT, SIGTERM, SIGTSTP])?;
+    std::thread::spawn(move || {
+        for signal in signals.forever() {
+            let event = match signal {
+                SIGTSTP => Event::Suspend,
+                _ => Event::Terminate,
+            };
+            if tx.send(event).is_err() {
+                break;
+            }
+        }
+    });
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn forward_signals<I: Send + 'static>(_tx: Sender<Event<I>>) -> io::Result<()> {
+    Ok(())
+}
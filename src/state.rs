@@ -0,0 +1,119 @@
+use std::collections::HashMap;
+
+use chrono::{Duration, Utc};
+use finish_it::{Forecast, History, Item, Store, StoreError, Topic};
+
+/// How far back the Activity tab can look, with room for its first partial week.
+const HISTORY_DAYS: i64 = 54 * 7 + 7;
+
+/// Everything the tabs show, read from the database once and kept until something changes.
+///
+/// Drawing and moving around only look at this. Changes made through the TUI call `reload`,
+/// selecting another Topic reads just its Items. Changes made from the command line or another
+/// instance show up through `is_outdated`, and opening the Topics or Activity tab reads
+/// everything again as well.
+#[derive(Debug, Default)]
+pub struct AppState {
+    pub topics: Vec<Topic>,
+    /// Items of the selected Topic.
+    pub items: Vec<Item>,
+    /// Forecasts of `items` by id, projected when they were read.
+    pub forecasts: HashMap<usize, Forecast>,
+    /// Everything logged during the last year, for the Activity tab.
+    pub history: Vec<History>,
+    /// Why the last read failed. Reading is retried until it works.
+    pub error: Option<String>,
+    /// Topic `items` belong to.
+    loaded_topic: Option<String>,
+    /// `Store::data_version` when everything was last read.
+    version: Option<i64>,
+    stale: bool,
+}
+
+impl AppState {
+    pub fn load(store: &dyn Store, selected_topic: Option<usize>) -> Self {
+        let mut state = AppState {
+            stale: true,
+            ..AppState::default()
+        };
+        state.sync(store, selected_topic);
+        state
+    }
+
+    /// Reads everything again, after the database was changed.
+    pub fn reload(
+        &mut self,
+        store: &dyn Store,
+        selected_topic: Option<usize>,
+    ) -> Result<(), StoreError> {
+        self.stale = true;
+        self.error = None;
+        let result = self.read(store, selected_topic);
+        if let Err(err) = &result {
+            self.error = Some(err.to_string());
+        }
+        result
+    }
+
+    /// Makes the next `sync` read everything again.
+    pub fn invalidate(&mut self) {
+        self.stale = true;
+    }
+
+    /// Whether someone else changed the database since everything was last read. A failing
+    /// check counts as no change, the next read reports the error anyway.
+    pub fn is_outdated(&self, store: &dyn Store) -> bool {
+        match store.data_version() {
+            Ok(version) => self.version.is_some_and(|seen| seen != version),
+            Err(_) => false,
+        }
+    }
+
+    /// Brings the Items up to date with the selected Topic and retries failed reads.
+    /// Does not touch the database otherwise, so it is cheap to call before every frame.
+    pub fn sync(&mut self, store: &dyn Store, selected_topic: Option<usize>) {
+        let topic = self.topic(selected_topic).map(|t| t.name.clone());
+        if self.stale || topic != self.loaded_topic {
+            self.error = self
+                .read(store, selected_topic)
+                .err()
+                .map(|err| err.to_string());
+        }
+    }
+
+    fn read(&mut self, store: &dyn Store, selected_topic: Option<usize>) -> Result<(), StoreError> {
+        if self.stale {
+            self.version = Some(store.data_version()?);
+            self.topics = store.topics()?;
+            self.history = store.history(Utc::now() - Duration::days(HISTORY_DAYS))?;
+        }
+        let topic = self.topic(selected_topic).map(|t| t.name.clone());
+        let topic_history = match &topic {
+            Some(name) => {
+                self.items = store.items(name)?;
+                store.topic_history(name)?
+            }
+            None => {
+                self.items = Vec::new();
+                Vec::new()
+            }
+        };
+        self.forecasts = self
+            .items
+            .iter()
+            .map(|item| (item.id, item.forecast(&topic_history)))
+            .collect();
+        self.loaded_topic = topic;
+        self.stale = false;
+        Ok(())
+    }
+
+    /// The Topic at `index`, or the first one if nothing is selected yet.
+    pub fn topic(&self, index: Option<usize>) -> Option<&Topic> {
+        self.topics.get(index.unwrap_or(0))
+    }
+
+    pub fn item(&self, index: Option<usize>) -> Option<&Item> {
+        self.items.get(index?)
+    }
+}
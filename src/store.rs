@@ -0,0 +1,74 @@
+//! The operations the TUI and the command line need from wherever Topics and Items are kept.
+
+use chrono::{DateTime, Utc};
+
+use crate::error::StoreError;
+use crate::model::{History, Item, Topic};
+
+/// Keeps Topics, their Items and the History of changes made to them.
+///
+/// [`Repository`](crate::Repository) keeps them in SQLite, [`MemoryStore`](crate::MemoryStore)
+/// only for as long as it lives. Every change is logged in the History, so both show the same
+/// Activity and reports.
+pub trait Store {
+    /// Every Topic, in the order they were added.
+    fn topics(&self) -> Result<Vec<Topic>, StoreError>;
+
+    /// The Items of the Topic called `topic`, ordered by id.
+    fn items(&self, topic: &str) -> Result<Vec<Item>, StoreError>;
+
+    /// Every Item, ordered by Topic and id.
+    fn all_items(&self) -> Result<Vec<Item>, StoreError>;
+
+    /// Changes made to any Item since `since`, oldest first.
+    fn history(&self, since: DateTime<Utc>) -> Result<Vec<History>, StoreError>;
+
+    /// Every change made to the Items currently in the Topic called `topic`, oldest first.
+    fn topic_history(&self, topic: &str) -> Result<Vec<History>, StoreError>;
+
+    /// Stores `item` as a new Item, adding its Topic if needed. Its id and creation time are
+    /// assigned by the Store. Invalid Items are refused, see [`check_item`].
+    fn add_item(&self, item: &Item) -> Result<(), StoreError>;
+
+    /// Imports `items` with all of their fields, overwriting the Items they were exported from, see
+    /// [`Item::is_same_item`]. Items with id 0, or with the id of a different Item, are stored as
+    /// new Items. A failing Item imports nothing.
+    fn import_items(&self, items: &[Item]) -> Result<usize, StoreError>;
+
+    /// Saves the progress of `item`, logging how much it changed. Invalid progress is refused,
+    /// see [`check_item`].
+    fn update_item(&self, item: &Item) -> Result<(), StoreError>;
+
+    /// Deletes the Item with the id of `item`.
+    fn delete_item(&self, item: &Item) -> Result<(), StoreError>;
+
+    /// Deletes `topic` along with all of its Items.
+    fn delete_topic(&self, topic: &Topic) -> Result<(), StoreError>;
+
+    /// A number that changes whenever someone else, like another program sharing the
+    /// database, changed the Topics or Items. Changes made through this Store leave it as it
+    /// is. Stores nobody else can change always return the same number.
+    fn data_version(&self) -> Result<i64, StoreError> {
+        Ok(0)
+    }
+}
+
+/// Refuses Items the database's CHECK constraints refuse, so every Store keeps the same rules:
+/// `isrecurring` is 0 or 1, `percentage` at most 100, and the counts fit SQLite's integers.
+pub fn check_item(item: &Item) -> Result<(), StoreError> {
+    let problem = if item.isrecurring > 1 {
+        format!("isrecurring is {}, expected 0 or 1", item.isrecurring)
+    } else if item.percentage > 100 {
+        format!("percentage is {}, expected 0 to 100", item.percentage)
+    } else if i64::try_from(item.timesfinished).is_err() {
+        format!("timesfinished is {}, expected less", item.timesfinished)
+    } else if i64::try_from(item.daylimit).is_err() {
+        format!("daylimit is {}, expected less", item.daylimit)
+    } else {
+        return Ok(());
+    };
+    Err(StoreError::Invalid(format!(
+        "Item \"{}\": {problem}",
+        item.name
+    )))
+}
@@ -0,0 +1,381 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Deserializer};
+use tui::style::{Color, Modifier, Style};
+
+/// Colors every widget is drawn with.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Body text and borders.
+    pub text: Color,
+    /// The "Finish It!" banner.
+    pub title: Color,
+    /// Key hints, the Menu and other things that should stand out.
+    pub accent: Color,
+    /// Text of the Update, Delete and Review popups.
+    pub popup: Color,
+    /// Background of the selection in the focused block.
+    pub selected: Color,
+    /// Background of the selection in the other block.
+    pub selected_inactive: Color,
+    /// Text on top of a selection.
+    pub selected_text: Color,
+    pub error: Color,
+    pub ok: Color,
+    /// Timestamps, inactive inputs and empty days.
+    pub muted: Color,
+    /// Activity heatmap cells with little and with a lot of progress.
+    pub heat_low: Color,
+    pub heat_high: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    pub const BUILT_IN: [&'static str; 4] = ["dark", "light", "high-contrast", "no-color"];
+
+    pub fn dark() -> Self {
+        Self {
+            text: Color::White,
+            title: Color::LightRed,
+            accent: Color::LightYellow,
+            popup: Color::LightCyan,
+            selected: Color::Red,
+            selected_inactive: Color::Yellow,
+            selected_text: Color::Black,
+            error: Color::LightRed,
+            ok: Color::LightGreen,
+            muted: Color::DarkGray,
+            heat_low: Color::Green,
+            heat_high: Color::LightGreen,
+        }
+    }
+
+    /// For terminals with a light background, where white text disappears.
+    pub fn light() -> Self {
+        Self {
+            text: Color::Black,
+            title: Color::Red,
+            accent: Color::Blue,
+            popup: Color::Magenta,
+            selected: Color::Blue,
+            selected_inactive: Color::Gray,
+            selected_text: Color::White,
+            error: Color::Red,
+            ok: Color::Green,
+            muted: Color::DarkGray,
+            heat_low: Color::LightGreen,
+            heat_high: Color::Green,
+        }
+    }
+
+    pub fn high_contrast() -> Self {
+        Self {
+            text: Color::White,
+            title: Color::White,
+            accent: Color::LightYellow,
+            popup: Color::White,
+            selected: Color::LightYellow,
+            selected_inactive: Color::White,
+            selected_text: Color::Black,
+            error: Color::LightRed,
+            ok: Color::LightGreen,
+            muted: Color::Gray,
+            heat_low: Color::LightGreen,
+            heat_high: Color::White,
+        }
+    }
+
+    /// Leaves every color to the terminal, selections are shown reversed instead.
+    pub fn no_color() -> Self {
+        Self {
+            text: Color::Reset,
+            title: Color::Reset,
+            accent: Color::Reset,
+            popup: Color::Reset,
+            selected: Color::Reset,
+            selected_inactive: Color::Reset,
+            selected_text: Color::Reset,
+            error: Color::Reset,
+            ok: Color::Reset,
+            muted: Color::Reset,
+            heat_low: Color::Reset,
+            heat_high: Color::Reset,
+        }
+    }
+
+    pub fn built_in(name: &str) -> Option<Self> {
+        match name {
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high-contrast" => Some(Theme::high_contrast()),
+            "no-color" => Some(Theme::no_color()),
+            _ => None,
+        }
+    }
+
+    /// Looks up the theme called `name` among the user themes and the built-in ones.
+    ///
+    /// Errors name the config key at fault, like `KeyMap::from_config`.
+    pub fn from_config(
+        name: &str,
+        themes: &HashMap<String, ThemeConfig>,
+    ) -> Result<Self, (String, String)> {
+        if let Some(overrides) = themes.get(name) {
+            let base = overrides.base.as_deref().unwrap_or("dark");
+            let theme = Theme::built_in(base).ok_or_else(|| {
+                (
+                    format!("themes.{name}.base"),
+                    format!(
+                        "must be one of {}, got '{base}'",
+                        Theme::BUILT_IN.join(", ")
+                    ),
+                )
+            })?;
+            return Ok(overrides.apply(theme));
+        }
+        Theme::built_in(name).ok_or_else(|| {
+            (
+                String::from("theme"),
+                format!(
+                    "must be one of {} or a theme from [themes], got '{name}'",
+                    Theme::BUILT_IN.join(", ")
+                ),
+            )
+        })
+    }
+
+    pub fn text(&self) -> Style {
+        Style::default().fg(self.text)
+    }
+
+    pub fn title(&self) -> Style {
+        Style::default().fg(self.title).add_modifier(Modifier::BOLD)
+    }
+
+    pub fn accent(&self) -> Style {
+        Style::default().fg(self.accent)
+    }
+
+    pub fn popup(&self) -> Style {
+        Style::default().fg(self.popup)
+    }
+
+    pub fn error(&self) -> Style {
+        Style::default().fg(self.error)
+    }
+
+    pub fn ok(&self) -> Style {
+        Style::default().fg(self.ok)
+    }
+
+    pub fn muted(&self) -> Style {
+        Style::default().fg(self.muted)
+    }
+
+    /// Style of the selected row, `focused` telling whether its block has the focus.
+    pub fn selection(&self, focused: bool) -> Style {
+        let bg = match focused {
+            true => self.selected,
+            false => self.selected_inactive,
+        };
+        let style = Style::default()
+            .bg(bg)
+            .fg(self.selected_text)
+            .add_modifier(Modifier::BOLD);
+        match bg {
+            Color::Reset => style.add_modifier(Modifier::REVERSED),
+            _ => style,
+        }
+    }
+
+    /// Style of the selected tab in the Menu.
+    pub fn active_tab(&self) -> Style {
+        match self.accent {
+            Color::Reset => Style::default().add_modifier(Modifier::REVERSED),
+            accent => Style::default().fg(accent),
+        }
+    }
+
+    /// Color of an Activity heatmap cell, `level` going from 0 (nothing done) to 4.
+    pub fn heat(&self, level: usize) -> Color {
+        match level {
+            0 => self.muted,
+            1 | 2 => self.heat_low,
+            _ => self.heat_high,
+        }
+    }
+}
+
+/// A color in the config file: a name like `light_red`, `#rrggbb` or a 256 color index.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThemeColor(Color);
+
+impl std::str::FromStr for ThemeColor {
+    type Err = String;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let color = match input.to_lowercase().replace(['-', ' '], "_").as_str() {
+            "reset" | "default" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "dark_gray" | "dark_grey" => Color::DarkGray,
+            "light_red" => Color::LightRed,
+            "light_green" => Color::LightGreen,
+            "light_yellow" => Color::LightYellow,
+            "light_blue" => Color::LightBlue,
+            "light_magenta" => Color::LightMagenta,
+            "light_cyan" => Color::LightCyan,
+            "white" => Color::White,
+            // Only ASCII digits can be sliced into channels by byte offsets.
+            hex if hex.len() == 7
+                && hex.starts_with('#')
+                && hex[1..].chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16);
+                match (channel(1), channel(3), channel(5)) {
+                    (Ok(r), Ok(g), Ok(b)) => Color::Rgb(r, g, b),
+                    _ => return Err(format!("invalid color '{input}'")),
+                }
+            }
+            index => match index.parse::<u8>() {
+                Ok(index) => Color::Indexed(index),
+                Err(_) => return Err(format!("invalid color '{input}'")),
+            },
+        };
+        Ok(ThemeColor(color))
+    }
+}
+
+impl<'de> Deserialize<'de> for ThemeColor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// A user theme from `[themes.<name>]`: a built-in base with some of its colors replaced.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+    /// Built-in theme the colors that are not given come from, `dark` if not set.
+    pub base: Option<String>,
+    pub text: Option<ThemeColor>,
+    pub title: Option<ThemeColor>,
+    pub accent: Option<ThemeColor>,
+    pub popup: Option<ThemeColor>,
+    pub selected: Option<ThemeColor>,
+    pub selected_inactive: Option<ThemeColor>,
+    pub selected_text: Option<ThemeColor>,
+    pub error: Option<ThemeColor>,
+    pub ok: Option<ThemeColor>,
+    pub muted: Option<ThemeColor>,
+    pub heat_low: Option<ThemeColor>,
+    pub heat_high: Option<ThemeColor>,
+}
+
+impl ThemeConfig {
+    fn apply(&self, mut theme: Theme) -> Theme {
+        for (color, replacement) in [
+            (&mut theme.text, self.text),
+            (&mut theme.title, self.title),
+            (&mut theme.accent, self.accent),
+            (&mut theme.popup, self.popup),
+            (&mut theme.selected, self.selected),
+            (&mut theme.selected_inactive, self.selected_inactive),
+            (&mut theme.selected_text, self.selected_text),
+            (&mut theme.error, self.error),
+            (&mut theme.ok, self.ok),
+            (&mut theme.muted, self.muted),
+            (&mut theme.heat_low, self.heat_low),
+            (&mut theme.heat_high, self.heat_high),
+        ] {
+            if let Some(ThemeColor(replacement)) = replacement {
+                *color = replacement;
+            }
+        }
+        theme
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn color(input: &str) -> Result<Color, String> {
+        input.parse::<ThemeColor>().map(|ThemeColor(color)| color)
+    }
+
+    #[test]
+    fn parses_names_hex_and_indices() {
+        assert_eq!(color("red"), Ok(Color::Red));
+        assert_eq!(color("Light-Blue"), Ok(Color::LightBlue));
+        assert_eq!(color("dark grey"), Ok(Color::DarkGray));
+        assert_eq!(color("default"), Ok(Color::Reset));
+        assert_eq!(color("#586e75"), Ok(Color::Rgb(0x58, 0x6e, 0x75)));
+        assert_eq!(color("#FFFFFF"), Ok(Color::Rgb(255, 255, 255)));
+        assert_eq!(color("0"), Ok(Color::Indexed(0)));
+        assert_eq!(color("255"), Ok(Color::Indexed(255)));
+    }
+
+    #[test]
+    fn rejects_invalid_colors() {
+        for input in [
+            "", "purple", "#12345", "#1234567", "#gggggg", "#+1+2+3", "#aéaaa", "#日本", "256",
+            "-1",
+        ] {
+            assert!(color(input).is_err(), "{input}");
+        }
+    }
+
+    #[test]
+    fn user_themes_replace_colors_of_their_base() {
+        let themes = HashMap::from([
+            (
+                String::from("solarized"),
+                ThemeConfig {
+                    base: Some(String::from("light")),
+                    text: color("#586e75").ok().map(ThemeColor),
+                    ..ThemeConfig::default()
+                },
+            ),
+            (
+                String::from("broken"),
+                ThemeConfig {
+                    base: Some(String::from("sepia")),
+                    ..ThemeConfig::default()
+                },
+            ),
+        ]);
+        let solarized = Theme::from_config("solarized", &themes).unwrap();
+        assert_eq!(solarized.text, Color::Rgb(0x58, 0x6e, 0x75));
+        assert_eq!(solarized.accent, Theme::light().accent);
+        assert_eq!(Theme::from_config("light", &themes), Ok(Theme::light()));
+
+        let (key, _) = Theme::from_config("broken", &themes).unwrap_err();
+        assert_eq!(key, "themes.broken.base");
+        let (key, _) = Theme::from_config("sepia", &themes).unwrap_err();
+        assert_eq!(key, "theme");
+    }
+
+    #[test]
+    fn selections_stay_visible_without_colors() {
+        let style = Theme::no_color().selection(true);
+        assert!(style.add_modifier.contains(Modifier::REVERSED));
+        assert!(!Theme::dark()
+            .selection(true)
+            .add_modifier
+            .contains(Modifier::REVERSED));
+    }
+}
@@ -0,0 +1,282 @@
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::Modifier,
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Tabs},
+    Frame,
+};
+
+use crate::activity::render_activity;
+use crate::add::{get_add_err_text, get_add_ok_text};
+use crate::app::App;
+use crate::keymap::{Action, KeyContext};
+use crate::mouse::{scroll_offset, Areas};
+use crate::render::*;
+use crate::{ActiveBlock, ActivePopUp, MenuItem};
+
+/// Shown under errors of widgets drawn from data that could not be read.
+const RETRY_HINT: &str = "Finish-it tries again on every redraw.";
+
+/// Draws the whole screen from `app` without changing it or touching the database.
+///
+/// Returns where the clickable widgets ended up, for the next mouse event to be matched against.
+pub fn draw<B: Backend>(app: &App, rect: &mut Frame<B>) -> Areas {
+    let theme = &app.config.palette;
+    let keymap = &app.config.keymap;
+    let size = rect.size();
+    let mut areas = app.areas.clone();
+    areas.clear();
+    if size.width < MIN_WIDTH || size.height < MIN_HEIGHT {
+        areas.tabs = Rect::default();
+        rect.render_widget(render_too_small(size, theme), size);
+        return areas;
+    }
+    let compact = size.width < COMPACT_WIDTH;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .margin(if compact { 0 } else { 2 })
+        .constraints(
+            [
+                Constraint::Length(3),
+                Constraint::Min(2),
+                Constraint::Length(3),
+            ]
+            .as_ref(),
+        )
+        .split(size);
+
+    let context = app.context();
+    let footer = Paragraph::new(app.config.keymap.hints(context))
+        .style(theme.accent())
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .style(theme.text())
+                .title(format!("Keys - {}", context.title()))
+                .border_type(BorderType::Plain),
+        );
+
+    let menu_titles: Vec<&str> = app
+        .config
+        .tabs
+        .iter()
+        .map(MenuItem::title)
+        .chain(["Quit"])
+        .collect();
+    let menu = menu_titles
+        .iter()
+        .map(|t| {
+            let (first, rest) = t.split_at(1);
+            Spans::from(vec![
+                Span::styled(first, theme.accent().add_modifier(Modifier::UNDERLINED)),
+                Span::styled(rest, theme.text()),
+            ])
+        })
+        .collect();
+
+    let tabs = Tabs::new(menu)
+        .select(
+            app.config
+                .tabs
+                .iter()
+                .position(|tab| *tab == app.active_menu_item)
+                .unwrap_or(0),
+        )
+        .block(
+            Block::default()
+                .title(match app.ephemeral {
+                    true => String::from("Menu - ephemeral"),
+                    false => format!("Menu - {}", app.config.profile),
+                })
+                .borders(Borders::ALL),
+        )
+        .style(theme.text())
+        .highlight_style(theme.active_tab())
+        .divider(Span::raw("|"));
+
+    rect.render_widget(tabs, chunks[0]);
+    areas.tabs = chunks[0];
+    areas.tab_titles = menu_titles.iter().map(|t| t.chars().count()).collect();
+
+    match app.active_menu_item {
+        MenuItem::Home => rect.render_widget(render_home(keymap, theme), chunks[1]),
+        MenuItem::Instances => 'topics: {
+            let topic_chunks = match compact {
+                true => Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Percentage(30), Constraint::Percentage(70)].as_ref())
+                    .split(chunks[1]),
+                false => Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(20), Constraint::Percentage(80)].as_ref())
+                    .split(chunks[1]),
+            };
+            if let Some(message) = &app.state.error {
+                rect.render_widget(render_error(message, RETRY_HINT, theme), chunks[1]);
+                break 'topics;
+            }
+            areas.topics = topic_chunks[0];
+            areas.topic_offset = scroll_offset(
+                areas.topic_offset,
+                app.topic_list_state.selected(),
+                app.state.topics.len(),
+                topic_chunks[0].height.saturating_sub(2),
+            );
+            areas.items = topic_chunks[1];
+            areas.item_offset = scroll_offset(
+                areas.item_offset,
+                app.item_list_state.selected(),
+                app.state.items.len(),
+                topic_chunks[1].height.saturating_sub(3),
+            );
+            let (left, mut left_state, right, mut right_state) =
+                render_topics(app, &areas, compact, theme);
+            rect.render_stateful_widget(left, topic_chunks[0], &mut left_state);
+            rect.render_stateful_widget(right, topic_chunks[1], &mut right_state);
+
+            match (app.active_block, app.active_popup) {
+                (ActiveBlock::InstanceBlock, ActivePopUp::Update) => {
+                    let block = render_update_popup(
+                        &app.selected_item,
+                        app.config.bar_width,
+                        keymap,
+                        theme,
+                    );
+
+                    let area = centered_rect(app.config.popup.width, app.config.popup.height, size);
+                    rect.render_widget(Clear, area);
+                    rect.render_widget(block, area);
+                }
+
+                (ActiveBlock::InstanceBlock, ActivePopUp::Delete) => {
+                    let block = render_delete_item_popup(&app.selected_item, keymap, theme);
+                    let area = centered_rect(app.config.popup.width, app.config.popup.height, size);
+                    rect.render_widget(Clear, area);
+                    rect.render_widget(block, area);
+                }
+
+                (ActiveBlock::Event, ActivePopUp::Delete) => {
+                    let block = render_delete_topic_popup(&app.selected_topic, keymap, theme);
+                    let area = centered_rect(app.config.popup.width, app.config.popup.height, size);
+                    rect.render_widget(Clear, area);
+                    rect.render_widget(block, area);
+                }
+
+                (ActiveBlock::Event, ActivePopUp::Update)
+                | (_, ActivePopUp::Review)
+                | (_, ActivePopUp::Profiles)
+                | (_, ActivePopUp::None) => {}
+            }
+        }
+        MenuItem::Add => {
+            // Narrow terminals get the help text under the inputs instead of beside them.
+            let cols = Layout::default()
+                .direction(match compact {
+                    true => Direction::Vertical,
+                    false => Direction::Horizontal,
+                })
+                .constraints(
+                    [
+                        Constraint::Percentage(if compact { 70 } else { 50 }),
+                        Constraint::Percentage(if compact { 30 } else { 50 }),
+                    ]
+                    .as_ref(),
+                )
+                .split(chunks[1]);
+            let layout_cols = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(20),
+                        Constraint::Percentage(15),
+                        Constraint::Percentage(15),
+                        Constraint::Percentage(15),
+                        Constraint::Percentage(15),
+                    ]
+                    .as_ref(),
+                )
+                .split(cols[0]);
+            for (ta, chunk) in app.text_areas.iter().zip(layout_cols) {
+                let widget = ta.text_area.widget();
+                rect.render_widget(widget, chunk);
+                areas.text_areas.push(chunk);
+            }
+            let helper_text = if !app.add_given_ok {
+                get_add_err_text(keymap, theme)
+            } else {
+                get_add_ok_text(keymap, theme)
+            };
+            rect.render_widget(helper_text, cols[1]);
+        }
+        MenuItem::Activity => {
+            let activity_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(12), Constraint::Min(3)].as_ref())
+                .split(chunks[1]);
+            if let Some(message) = &app.state.error {
+                rect.render_widget(render_error(message, RETRY_HINT, theme), chunks[1]);
+            } else {
+                let (heatmap, day_list) = render_activity(
+                    &app.state.history,
+                    app.activity_scope,
+                    &app.selected_topic,
+                    &app.selected_item,
+                    app.activity_day,
+                    activity_chunks[0].width,
+                    keymap,
+                    theme,
+                );
+                rect.render_widget(heatmap, activity_chunks[0]);
+                rect.render_widget(day_list, activity_chunks[1]);
+            }
+        }
+    }
+    if app.active_popup == ActivePopUp::Review {
+        let block = Paragraph::new(app.review_text.as_str())
+            .style(theme.popup())
+            .scroll((app.review_scroll, 0))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .style(theme.text())
+                    .title(format!(
+                        "Review of the last {} days ({}: week/month, {}/{}: scroll, {}: close)",
+                        app.review_days,
+                        keymap.describe(Action::Range, KeyContext::ReviewPopup),
+                        keymap.describe(Action::Up, KeyContext::ReviewPopup),
+                        keymap.describe(Action::Down, KeyContext::ReviewPopup),
+                        keymap.describe(Action::Cancel, KeyContext::ReviewPopup)
+                    ))
+                    .border_type(BorderType::Plain),
+            );
+        let area = centered_rect(70, 70, size);
+        rect.render_widget(Clear, area);
+        rect.render_widget(block, area);
+    }
+    if app.active_popup == ActivePopUp::Profiles {
+        let list = render_profiles_popup(&app.profiles, &app.config.profile, theme);
+        let area = centered_rect(app.config.popup.width, app.config.popup.height, size);
+        let mut state = app.profile_list_state.clone();
+        rect.render_widget(Clear, area);
+        rect.render_stateful_widget(list, area, &mut state);
+    }
+    if app.show_help {
+        let area = centered_rect(app.config.popup.width, 60, size);
+        rect.render_widget(Clear, area);
+        rect.render_widget(
+            render_help(&app.config.keymap, app.help_context, theme),
+            area,
+        );
+    }
+    if let Some(message) = &app.error_message {
+        let hint = app.config.keymap.hints(KeyContext::ErrorPopup);
+        let area = centered_rect(app.config.popup.width, app.config.popup.height, size);
+        rect.render_widget(Clear, area);
+        rect.render_widget(render_error(message, &hint, theme), area);
+    }
+    rect.render_widget(footer, chunks[2]);
+    areas
+}